@@ -0,0 +1,67 @@
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn main() {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    println!("cargo:rustc-env=BUILD_TIMESTAMP={}", timestamp);
+
+    let commit = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|out| out.status.success())
+        .and_then(|out| String::from_utf8(out.stdout).ok())
+        .map(|s| s.trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=GIT_COMMIT={}", commit);
+    rerun_on_new_commit();
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=Cargo.lock");
+
+    println!("cargo:rustc-env=SOLANA_SDK_VERSION={}", locked_version("solana-sdk"));
+}
+
+/// Reruns this script when HEAD moves: on a checkout, which rewrites
+/// `.git/HEAD`, or on a commit, which rewrites the ref HEAD points to.
+fn rerun_on_new_commit() {
+    let git_dir = Command::new("git")
+        .args(["rev-parse", "--git-dir"])
+        .output()
+        .ok()
+        .filter(|out| out.status.success())
+        .and_then(|out| String::from_utf8(out.stdout).ok())
+        .map(|s| Path::new(s.trim()).to_path_buf());
+    let Some(git_dir) = git_dir else {
+        return;
+    };
+
+    let head = git_dir.join("HEAD");
+    println!("cargo:rerun-if-changed={}", head.display());
+    let head = std::fs::read_to_string(head).unwrap_or_default();
+    if let Some(reference) = head.trim().strip_prefix("ref: ") {
+        println!("cargo:rerun-if-changed={}", git_dir.join(reference).display());
+        // Where the ref lives once `git gc` has packed it.
+        println!("cargo:rerun-if-changed={}", git_dir.join("packed-refs").display());
+    }
+}
+
+/// Looks up the resolved version of a dependency in Cargo.lock.
+fn locked_version(package: &str) -> String {
+    let lock_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.lock");
+    let lock = std::fs::read_to_string(lock_path).unwrap_or_default();
+    let needle = format!("name = \"{}\"", package);
+
+    let mut lines = lock.lines();
+    while let Some(line) = lines.next() {
+        if line.trim() == needle {
+            if let Some(version) = lines.next().and_then(|l| l.trim().strip_prefix("version = ")) {
+                return version.trim_matches('"').to_string();
+            }
+        }
+    }
+    "unknown".to_string()
+}
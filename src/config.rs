@@ -0,0 +1,339 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::Duration;
+
+use solana_sdk::{packet::PACKET_DATA_SIZE, pubkey::Pubkey};
+
+use crate::keys::ServerKeys;
+use crate::types::TokenProgram;
+
+const DEFAULT_MAX_MESSAGE_BYTES: usize = 1024 * 1024;
+const DEFAULT_MAX_TRANSACTION_BYTES: usize = PACKET_DATA_SIZE;
+/// axum's default request-body limit, which every non-batch route uses.
+pub const MAX_BODY_BYTES: usize = 2 * 1024 * 1024;
+const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 30;
+const DEFAULT_MAX_BATCH_SIZE: usize = 100;
+const DEFAULT_CONFIRM_TIMEOUT_SECS: u64 = 20;
+const DEFAULT_MAX_RPC_TIMEOUT_MS: u64 = 20_000;
+const DEFAULT_IDEMPOTENCY_TTL_SECS: u64 = 60 * 60;
+const DEFAULT_GRIND_MAX_ATTEMPTS: u64 = 10_000_000;
+const DEFAULT_GRIND_TIMEOUT_SECS: u64 = 10;
+const DEFAULT_GRIND_MAX_CONCURRENT: usize = 1;
+const DEFAULT_TOKEN_DECIMALS: u8 = 6;
+const DEFAULT_TCP_BACKLOG: u32 = 1024;
+const DEFAULT_TCP_KEEPALIVE_SECS: u64 = 60;
+
+/// Runtime settings, read from the environment at startup and again on
+/// `POST /admin/reload`.
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Largest message (in bytes) the signing endpoints will accept.
+    pub max_message_bytes: usize,
+    /// Largest decoded transaction the transaction endpoints will parse.
+    /// Defaults to the wire limit of one packet; its base64 form must still
+    /// fit in a request body.
+    pub max_transaction_bytes: usize,
+    /// Deadline for RPC-backed requests; exceeded requests get a 504. Applied
+    /// when the router is built, so changing it needs a restart.
+    pub request_timeout: Duration,
+    /// How long `/transaction/send-and-confirm` waits for confirmation before
+    /// answering with the unconfirmed signature. Must stay below
+    /// `request_timeout`, or the 504 would arrive first and drop the signature.
+    pub confirm_timeout: Duration,
+    /// Upper bound for a request's `timeoutMs`. Must stay below
+    /// `request_timeout` for the same reason as `confirm_timeout`.
+    pub max_rpc_timeout: Duration,
+    /// How long a result stays replayable under its `Idempotency-Key`.
+    pub idempotency_ttl: Duration,
+    /// Largest number of items any batch endpoint accepts in one request.
+    pub max_batch_size: usize,
+    /// Token program used when a request omits `tokenProgram`
+    /// (`DEFAULT_TOKEN_PROGRAM=token|token-2022`).
+    pub default_token_program: TokenProgram,
+    /// `DEFAULT_TOKEN_DECIMALS`: the decimals `/send/token` puts in its
+    /// `transferChecked`, which doesn't read the mint. A fallback for servers
+    /// handling one known token, not a guarantee: if it doesn't match the
+    /// mint, the transfer fails on-chain.
+    pub default_token_decimals: u8,
+    /// `None` when `RPC_URL` is unset; the offline endpoints work regardless.
+    pub rpc_url: Option<String>,
+    /// Required in the `x-admin-token` header by the admin endpoints, which
+    /// are disabled while it is unset.
+    pub admin_token: Option<String>,
+    /// `ALLOW_KEY_SUBMISSION`: enables `/keypair/sign-and-send`, which signs
+    /// with client-supplied secrets and submits. Off by default.
+    pub allow_key_submission: bool,
+    /// `ALLOW_SERVER_KEYS`: lets signing endpoints take `keyAlias` in place
+    /// of `secret`. Off by default.
+    pub allow_server_keys: bool,
+    /// `SERVER_KEYS`: comma-separated `alias=path` keypair files, read only
+    /// while `ALLOW_SERVER_KEYS` is on.
+    pub server_keys: ServerKeys,
+    /// Blocking threads `/keypair/grind` searches on; defaults to the number
+    /// of CPUs.
+    pub grind_threads: usize,
+    /// Keypairs one `/keypair/grind` request may generate before giving up.
+    pub grind_max_attempts: u64,
+    /// Wall-clock limit for one `/keypair/grind` request.
+    pub grind_timeout: Duration,
+    /// Grinds that may run at once, each on `grind_threads` threads; more get
+    /// a 503. Sized at startup, so changing it needs a restart.
+    pub grind_max_concurrent: usize,
+    /// `ENABLED_ENDPOINTS`: when set, only these comma-separated route paths
+    /// are mounted. Mutually exclusive with `DISABLED_ENDPOINTS`.
+    pub enabled_endpoints: Option<Vec<String>>,
+    /// `DISABLED_ENDPOINTS`: comma-separated route paths left unmounted.
+    pub disabled_endpoints: Vec<String>,
+    /// Pending-connection queue length for the listening socket.
+    pub tcp_backlog: u32,
+    /// Idle time before TCP keepalive probes start; `TCP_KEEPALIVE_SECS=0`
+    /// disables keepalive.
+    pub tcp_keepalive: Option<Duration>,
+    /// Disables Nagle's algorithm on accepted connections.
+    pub tcp_nodelay: bool,
+    /// `MINT_WHITELIST`: when set, the token endpoints only build
+    /// instructions for these comma-separated mints.
+    pub mint_whitelist: Option<Vec<Pubkey>>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("invalid value for {key}: {value:?}")]
+    Invalid { key: &'static str, value: String },
+    #[error("failed to read CONFIG_FILE {path}: {source}")]
+    File {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("failed to read SERVER_KEYS file {path} for {alias}: {reason}")]
+    KeyFile {
+        alias: String,
+        path: String,
+        reason: String,
+    },
+}
+
+impl Config {
+    /// Reads the process environment, overlaid by the `KEY=VALUE` lines of
+    /// `CONFIG_FILE` when set. The file is what makes a reload useful: the
+    /// environment of a running process can't be changed from outside.
+    pub fn load() -> Result<Self, ConfigError> {
+        let source = Source::new()?;
+        let allow_server_keys = source.parse("ALLOW_SERVER_KEYS", false)?;
+        let server_keys = match allow_server_keys {
+            true => ServerKeys::load(source.list("SERVER_KEYS").unwrap_or_default())?,
+            false => ServerKeys::default(),
+        };
+        let config = Self {
+            max_message_bytes: source.parse("MAX_MESSAGE_BYTES", DEFAULT_MAX_MESSAGE_BYTES)?,
+            max_transaction_bytes: source
+                .parse("MAX_TRANSACTION_BYTES", DEFAULT_MAX_TRANSACTION_BYTES)?,
+            request_timeout: Duration::from_secs(
+                source.parse("REQUEST_TIMEOUT_SECS", DEFAULT_REQUEST_TIMEOUT_SECS)?,
+            ),
+            confirm_timeout: Duration::from_secs(
+                source.parse("CONFIRM_TIMEOUT_SECS", DEFAULT_CONFIRM_TIMEOUT_SECS)?,
+            ),
+            max_rpc_timeout: Duration::from_millis(
+                source.parse("MAX_RPC_TIMEOUT_MS", DEFAULT_MAX_RPC_TIMEOUT_MS)?,
+            ),
+            idempotency_ttl: Duration::from_secs(
+                source.parse("IDEMPOTENCY_TTL_SECS", DEFAULT_IDEMPOTENCY_TTL_SECS)?,
+            ),
+            max_batch_size: source.parse("MAX_BATCH_SIZE", DEFAULT_MAX_BATCH_SIZE)?,
+            default_token_program: source
+                .parse("DEFAULT_TOKEN_PROGRAM", TokenProgram::default())?,
+            default_token_decimals: source
+                .parse("DEFAULT_TOKEN_DECIMALS", DEFAULT_TOKEN_DECIMALS)?,
+            rpc_url: source.string("RPC_URL"),
+            admin_token: source.string("ADMIN_TOKEN"),
+            allow_key_submission: source.parse("ALLOW_KEY_SUBMISSION", false)?,
+            allow_server_keys,
+            server_keys,
+            grind_threads: source.parse(
+                "GRIND_THREADS",
+                std::thread::available_parallelism().map_or(1, |n| n.get()),
+            )?,
+            grind_max_attempts: source.parse("GRIND_MAX_ATTEMPTS", DEFAULT_GRIND_MAX_ATTEMPTS)?,
+            grind_timeout: Duration::from_secs(
+                source.parse("GRIND_TIMEOUT_SECS", DEFAULT_GRIND_TIMEOUT_SECS)?,
+            ),
+            grind_max_concurrent: source
+                .parse("GRIND_MAX_CONCURRENT", DEFAULT_GRIND_MAX_CONCURRENT)?,
+            enabled_endpoints: source.list("ENABLED_ENDPOINTS"),
+            disabled_endpoints: source.list("DISABLED_ENDPOINTS").unwrap_or_default(),
+            tcp_backlog: source.parse("TCP_BACKLOG", DEFAULT_TCP_BACKLOG)?,
+            tcp_keepalive: Some(source.parse("TCP_KEEPALIVE_SECS", DEFAULT_TCP_KEEPALIVE_SECS)?)
+                .filter(|&secs| secs > 0)
+                .map(Duration::from_secs),
+            tcp_nodelay: source.parse("TCP_NODELAY", true)?,
+            mint_whitelist: source.pubkeys("MINT_WHITELIST")?,
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        let zero = |key| ConfigError::Invalid {
+            key,
+            value: "0".to_string(),
+        };
+        if self.max_message_bytes == 0 {
+            return Err(zero("MAX_MESSAGE_BYTES"));
+        }
+        if self.max_transaction_bytes == 0 || self.max_transaction_bytes / 3 * 4 > MAX_BODY_BYTES {
+            return Err(ConfigError::Invalid {
+                key: "MAX_TRANSACTION_BYTES",
+                value: self.max_transaction_bytes.to_string(),
+            });
+        }
+        if self.request_timeout.is_zero() {
+            return Err(zero("REQUEST_TIMEOUT_SECS"));
+        }
+        if self.confirm_timeout.is_zero() || self.confirm_timeout >= self.request_timeout {
+            return Err(ConfigError::Invalid {
+                key: "CONFIRM_TIMEOUT_SECS",
+                value: self.confirm_timeout.as_secs().to_string(),
+            });
+        }
+        if self.max_rpc_timeout.is_zero() || self.max_rpc_timeout >= self.request_timeout {
+            return Err(ConfigError::Invalid {
+                key: "MAX_RPC_TIMEOUT_MS",
+                value: self.max_rpc_timeout.as_millis().to_string(),
+            });
+        }
+        if self.max_batch_size == 0 {
+            return Err(zero("MAX_BATCH_SIZE"));
+        }
+        if self.grind_threads == 0 {
+            return Err(zero("GRIND_THREADS"));
+        }
+        if self.grind_max_attempts == 0 {
+            return Err(zero("GRIND_MAX_ATTEMPTS"));
+        }
+        if self.grind_timeout.is_zero() {
+            return Err(zero("GRIND_TIMEOUT_SECS"));
+        }
+        if self.grind_max_concurrent == 0 {
+            return Err(zero("GRIND_MAX_CONCURRENT"));
+        }
+        if self.enabled_endpoints.is_some() && !self.disabled_endpoints.is_empty() {
+            return Err(ConfigError::Invalid {
+                key: "DISABLED_ENDPOINTS",
+                value: "set together with ENABLED_ENDPOINTS".to_string(),
+            });
+        }
+        if self.tcp_backlog == 0 {
+            return Err(zero("TCP_BACKLOG"));
+        }
+        Ok(())
+    }
+}
+
+/// The live config, shared with `AppState` so a reload is seen everywhere.
+pub type SharedConfig = Arc<RwLock<Arc<Config>>>;
+
+static SHARED: OnceLock<SharedConfig> = OnceLock::new();
+
+/// Installs the startup config. Must run before the first `get()`.
+pub fn init(config: Config) -> SharedConfig {
+    SHARED
+        .get_or_init(|| Arc::new(RwLock::new(Arc::new(config))))
+        .clone()
+}
+
+/// Installs the environment's config with `RPC_URL` cleared, so tests never
+/// reach a node. The first call wins for the whole test binary.
+#[cfg(test)]
+pub fn init_for_tests() -> SharedConfig {
+    let mut config = Config::load().expect("load config");
+    config.rpc_url = None;
+    init(config)
+}
+
+/// A snapshot of the current config; a concurrent reload doesn't change it.
+pub fn get() -> Arc<Config> {
+    SHARED
+        .get()
+        .expect("config::init must run before config::get")
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+}
+
+/// Process environment plus the optional `CONFIG_FILE` overrides.
+struct Source {
+    file: HashMap<String, String>,
+}
+
+impl Source {
+    fn new() -> Result<Self, ConfigError> {
+        let file = match std::env::var("CONFIG_FILE") {
+            Ok(path) if !path.trim().is_empty() => {
+                let contents = std::fs::read_to_string(&path)
+                    .map_err(|source| ConfigError::File { path, source })?;
+                parse_config_file(&contents)
+            }
+            _ => HashMap::new(),
+        };
+        Ok(Self { file })
+    }
+
+    fn string(&self, key: &str) -> Option<String> {
+        self.file
+            .get(key)
+            .cloned()
+            .or_else(|| std::env::var(key).ok())
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    }
+
+    /// A comma-separated list; empty entries are dropped.
+    fn list(&self, key: &str) -> Option<Vec<String>> {
+        self.string(key).map(|value| {
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+    }
+
+    /// A comma-separated list of base58 pubkeys; one bad entry fails the load.
+    fn pubkeys(&self, key: &'static str) -> Result<Option<Vec<Pubkey>>, ConfigError> {
+        self.list(key)
+            .map(|values| {
+                values
+                    .into_iter()
+                    .map(|value| {
+                        Pubkey::from_str(&value).map_err(|_| ConfigError::Invalid { key, value })
+                    })
+                    .collect()
+            })
+            .transpose()
+    }
+
+    /// Parses `key`, falling back to `default` when unset; an unparseable
+    /// value is an error rather than silently ignored.
+    fn parse<T: FromStr>(&self, key: &'static str, default: T) -> Result<T, ConfigError> {
+        match self.string(key) {
+            None => Ok(default),
+            Some(value) => value
+                .parse()
+                .map_err(|_| ConfigError::Invalid { key, value }),
+        }
+    }
+}
+
+/// `KEY=VALUE` per line; blank lines and `#` comments are skipped.
+fn parse_config_file(contents: &str) -> HashMap<String, String> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .collect()
+}
@@ -0,0 +1,85 @@
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+
+use axum::{routing::MethodRouter, Router};
+
+use crate::config::{Config, ConfigError};
+
+/// Decides which routes get mounted, from `ENABLED_ENDPOINTS` (an allowlist)
+/// or `DISABLED_ENDPOINTS`. Names are route paths such as `/keypair/grind`.
+/// Skipped routes fall through to the JSON 404. Routes are mounted once at
+/// startup, so changing either list needs a restart.
+pub struct EndpointFilter {
+    enabled: Option<BTreeSet<String>>,
+    disabled: BTreeSet<String>,
+    /// Every path offered for mounting, to catch typos in the lists.
+    seen: RefCell<BTreeSet<&'static str>>,
+}
+
+impl EndpointFilter {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            enabled: config
+                .enabled_endpoints
+                .as_ref()
+                .map(|names| names.iter().cloned().collect()),
+            disabled: config.disabled_endpoints.iter().cloned().collect(),
+            seen: RefCell::new(BTreeSet::new()),
+        }
+    }
+
+    pub fn router<S: Clone + Send + Sync + 'static>(&self) -> Routes<'_, S> {
+        Routes {
+            router: Router::new(),
+            filter: self,
+        }
+    }
+
+    fn is_enabled(&self, path: &'static str) -> bool {
+        self.seen.borrow_mut().insert(path);
+        let allowed = self
+            .enabled
+            .as_ref()
+            .is_none_or(|enabled| enabled.contains(path));
+        allowed && !self.disabled.contains(path)
+    }
+
+    /// Fails on any listed name that isn't a route. Call after every route
+    /// has been offered.
+    pub fn check(&self) -> Result<(), ConfigError> {
+        let seen = self.seen.borrow();
+        let lists = [
+            ("ENABLED_ENDPOINTS", self.enabled.as_ref()),
+            ("DISABLED_ENDPOINTS", Some(&self.disabled)),
+        ];
+        for (key, names) in lists {
+            let mut names = names.into_iter().flatten();
+            if let Some(name) = names.find(|name| !seen.contains(name.as_str())) {
+                return Err(ConfigError::Invalid {
+                    key,
+                    value: name.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A `Router` that only mounts the routes its filter allows.
+pub struct Routes<'a, S> {
+    router: Router<S>,
+    filter: &'a EndpointFilter,
+}
+
+impl<S: Clone + Send + Sync + 'static> Routes<'_, S> {
+    pub fn route(mut self, path: &'static str, handler: MethodRouter<S>) -> Self {
+        if self.filter.is_enabled(path) {
+            self.router = self.router.route(path, handler);
+        }
+        self
+    }
+
+    pub fn into_router(self) -> Router<S> {
+        self.router
+    }
+}
@@ -0,0 +1,199 @@
+use axum::{
+    http::{header::RETRY_AFTER, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+
+/// Stable, machine-readable error codes. Clients should branch on these
+/// rather than on the human-readable `error` message, which may change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    InvalidPubkey,
+    DefaultPubkey,
+    InvalidSecret,
+    InvalidMnemonic,
+    InvalidPattern,
+    InvalidSignature,
+    InvalidMessage,
+    InvalidEncoding,
+    UnsupportedAlgorithm,
+    MessageTooLarge,
+    TransactionTooLarge,
+    SignerNotInMessage,
+    MissingSigner,
+    InvalidTransaction,
+    InvalidBlockhash,
+    InvalidAmount,
+    AmountZero,
+    AmountOverflow,
+    InstructionBuildFailed,
+    UnsupportedExtension,
+    BatchTooLarge,
+    InvalidIdempotencyKey,
+    InvalidTimeout,
+    IdempotencyConflict,
+    InvalidAccount,
+    InvalidMultisig,
+    InvalidConfig,
+    ConflictingFields,
+    UnknownKeyAlias,
+    Unauthorized,
+    EndpointDisabled,
+    MintNotAllowed,
+    RouteNotFound,
+    RpcError,
+    RpcRateLimited,
+    RpcNotConfigured,
+    RequestTimeout,
+    ServerBusy,
+    InternalError,
+}
+
+impl ErrorCode {
+    pub fn status(self) -> StatusCode {
+        match self {
+            ErrorCode::InvalidPubkey
+            | ErrorCode::DefaultPubkey
+            | ErrorCode::InvalidSecret
+            | ErrorCode::InvalidMnemonic
+            | ErrorCode::InvalidPattern
+            | ErrorCode::InvalidSignature
+            | ErrorCode::InvalidMessage
+            | ErrorCode::InvalidEncoding
+            | ErrorCode::UnsupportedAlgorithm
+            | ErrorCode::SignerNotInMessage
+            | ErrorCode::MissingSigner
+            | ErrorCode::InvalidTransaction
+            | ErrorCode::InvalidBlockhash
+            | ErrorCode::InvalidAmount
+            | ErrorCode::AmountZero
+            | ErrorCode::AmountOverflow
+            | ErrorCode::InstructionBuildFailed
+            | ErrorCode::UnsupportedExtension
+            | ErrorCode::BatchTooLarge
+            | ErrorCode::InvalidIdempotencyKey
+            | ErrorCode::InvalidTimeout
+            | ErrorCode::InvalidAccount
+            | ErrorCode::InvalidMultisig
+            | ErrorCode::InvalidConfig
+            | ErrorCode::ConflictingFields
+            | ErrorCode::UnknownKeyAlias => StatusCode::BAD_REQUEST,
+            ErrorCode::Unauthorized => StatusCode::UNAUTHORIZED,
+            ErrorCode::EndpointDisabled | ErrorCode::MintNotAllowed => StatusCode::FORBIDDEN,
+            ErrorCode::IdempotencyConflict => StatusCode::CONFLICT,
+            ErrorCode::RouteNotFound => StatusCode::NOT_FOUND,
+            ErrorCode::MessageTooLarge | ErrorCode::TransactionTooLarge => {
+                StatusCode::PAYLOAD_TOO_LARGE
+            }
+            ErrorCode::RpcError => StatusCode::BAD_GATEWAY,
+            ErrorCode::RpcRateLimited => StatusCode::TOO_MANY_REQUESTS,
+            ErrorCode::RpcNotConfigured => StatusCode::SERVICE_UNAVAILABLE,
+            ErrorCode::RequestTimeout => StatusCode::GATEWAY_TIMEOUT,
+            ErrorCode::ServerBusy => StatusCode::SERVICE_UNAVAILABLE,
+            ErrorCode::InternalError => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("{message}")]
+pub struct AppError {
+    pub code: ErrorCode,
+    pub message: String,
+    /// Every invalid field, for handlers that validate all fields before
+    /// failing. `code` and `message` are then those of the first one.
+    pub fields: Vec<FieldError>,
+    /// Sent as the `Retry-After` header, e.g. the upstream value on a
+    /// rate-limited RPC call.
+    pub retry_after: Option<String>,
+}
+
+impl AppError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            fields: Vec::new(),
+            retry_after: None,
+        }
+    }
+}
+
+/// One invalid request field in an error body's `fields`.
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub error: String,
+    pub code: ErrorCode,
+}
+
+/// Collects field failures so a handler can report every invalid field in
+/// one response instead of stopping at the first.
+#[derive(Debug, Default)]
+pub struct FieldErrors(Vec<FieldError>);
+
+impl FieldErrors {
+    /// The value of `result`, or `None` after recording its error for `field`.
+    pub fn check<T>(&mut self, field: &str, result: Result<T, AppError>) -> Option<T> {
+        result
+            .map_err(|e| {
+                self.0.push(FieldError {
+                    field: field.to_string(),
+                    error: e.message,
+                    code: e.code,
+                })
+            })
+            .ok()
+    }
+
+    /// The error to return once a `check` has failed.
+    pub fn into_error(self) -> AppError {
+        let Some(first) = self.0.first() else {
+            return AppError::new(ErrorCode::InternalError, "validation failed without an error");
+        };
+        AppError {
+            code: first.code,
+            message: first.error.clone(),
+            fields: self.0,
+            retry_after: None,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct ErrorBody<'a> {
+    success: bool,
+    error: &'a str,
+    code: ErrorCode,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    fields: &'a [FieldError],
+}
+
+impl AppError {
+    /// The JSON error body, without the status. Used where the status line has
+    /// already been sent, such as mid-way through a streamed response.
+    pub fn body(&self) -> ErrorBody<'_> {
+        ErrorBody {
+            success: false,
+            error: &self.message,
+            code: self.code,
+            fields: &self.fields,
+        }
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let mut response = (self.code.status(), Json(self.body())).into_response();
+        if let Some(value) = self
+            .retry_after
+            .as_deref()
+            .and_then(|value| HeaderValue::from_str(value).ok())
+        {
+            response.headers_mut().insert(RETRY_AFTER, value);
+        }
+        response
+    }
+}
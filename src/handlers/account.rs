@@ -0,0 +1,57 @@
+use axum::{extract::State, Json};
+use serde::{Deserialize, Serialize};
+
+use crate::response::{ApiResponse, HandlerResult};
+use crate::rpc::{fetch_account, rpc_error, within};
+use crate::state::AppState;
+use crate::types::{parse_pubkey, Commitment};
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountExistsRequest {
+    pub pubkey: String,
+    /// Defaults to the RPC client's commitment (finalized).
+    #[serde(default)]
+    pub commitment: Option<Commitment>,
+    /// Deadline for each RPC call, in milliseconds; clamped to
+    /// `MAX_RPC_TIMEOUT_MS`.
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountExistsData {
+    pub exists: bool,
+    pub pubkey: String,
+    /// Omitted when the account doesn't exist.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lamports: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+}
+
+/// Whether an account exists on chain, so a client can decide whether to add
+/// a create instruction. A missing account is `exists: false`, not an error.
+pub async fn account_exists(
+    State(state): State<AppState>,
+    Json(payload): Json<AccountExistsRequest>,
+) -> HandlerResult<AccountExistsData> {
+    let pubkey = parse_pubkey(&payload.pubkey, "pubkey")?;
+
+    let rpc = state.rpc()?;
+    let commitment = payload
+        .commitment
+        .map(Into::into)
+        .unwrap_or_else(|| rpc.commitment());
+    let account = within(payload.timeout_ms, fetch_account(&rpc, &pubkey, commitment))
+        .await?
+        .map_err(rpc_error)?;
+
+    Ok(ApiResponse::from(AccountExistsData {
+        exists: account.is_some(),
+        pubkey: pubkey.to_string(),
+        lamports: account.as_ref().map(|account| account.lamports),
+        owner: account.map(|account| account.owner.to_string()),
+    }))
+}
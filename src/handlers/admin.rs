@@ -0,0 +1,67 @@
+use axum::{extract::State, http::HeaderMap};
+use serde::Serialize;
+
+use crate::config::{self, Config};
+use crate::error::{AppError, ErrorCode};
+use crate::response::{ApiResponse, HandlerResult};
+use crate::state::AppState;
+use crate::types::TokenProgram;
+
+pub const ADMIN_TOKEN_HEADER: &str = "x-admin-token";
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReloadData {
+    pub max_message_bytes: usize,
+    pub max_batch_size: usize,
+    pub default_token_program: TokenProgram,
+    pub rpc_configured: bool,
+}
+
+/// Re-reads the environment and `CONFIG_FILE` and swaps the result in. An
+/// invalid config is rejected and the running one stays in place.
+pub async fn reload_config(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> HandlerResult<ReloadData> {
+    authorize(&headers)?;
+
+    let config = Config::load()
+        .map_err(|e| AppError::new(ErrorCode::InvalidConfig, e.to_string()))?;
+    let data = ReloadData {
+        max_message_bytes: config.max_message_bytes,
+        max_batch_size: config.max_batch_size,
+        default_token_program: config.default_token_program,
+        rpc_configured: config.rpc_url.is_some(),
+    };
+    state.apply(config);
+
+    Ok(ApiResponse::from(data))
+}
+
+fn authorize(headers: &HeaderMap) -> Result<(), AppError> {
+    let Some(expected) = config::get().admin_token.clone() else {
+        return Err(AppError::new(
+            ErrorCode::Unauthorized,
+            "Admin endpoints are disabled: set ADMIN_TOKEN to enable them",
+        ));
+    };
+    let provided = headers
+        .get(ADMIN_TOKEN_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+
+    if !constant_time_eq(provided.as_bytes(), expected.as_bytes()) {
+        return Err(AppError::new(
+            ErrorCode::Unauthorized,
+            "Missing or invalid x-admin-token header",
+        ));
+    }
+    Ok(())
+}
+
+/// Compares without short-circuiting so response timing doesn't reveal how
+/// much of the token matched.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
@@ -0,0 +1,145 @@
+use axum::{extract::State, Json};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, ErrorCode};
+use crate::response::{ApiResponse, HandlerResult};
+use crate::state::AppState;
+use crate::types::{checked_add_amount, checked_mul_amount};
+
+/// ------------------ /token/amount/ui-to-raw ------------------
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UiToRawRequest {
+    /// Decimal string rather than a float so no precision is lost in transit.
+    pub ui_amount: String,
+    pub decimals: u8,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RawAmountData {
+    pub amount: u64,
+    pub decimals: u8,
+}
+
+pub async fn ui_to_raw(
+    State(_): State<AppState>,
+    Json(payload): Json<UiToRawRequest>,
+) -> HandlerResult<RawAmountData> {
+    let amount = ui_amount_to_raw(&payload.ui_amount, payload.decimals)?;
+
+    Ok(ApiResponse::from(RawAmountData {
+        amount,
+        decimals: payload.decimals,
+    }))
+}
+
+/// ------------------ /token/amount/raw-to-ui ------------------
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RawToUiRequest {
+    pub amount: u64,
+    pub decimals: u8,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UiAmountData {
+    pub ui_amount: String,
+    pub decimals: u8,
+}
+
+pub async fn raw_to_ui(
+    State(_): State<AppState>,
+    Json(payload): Json<RawToUiRequest>,
+) -> ApiResponse<UiAmountData> {
+    ApiResponse::from(UiAmountData {
+        ui_amount: raw_amount_to_ui(payload.amount, payload.decimals),
+        decimals: payload.decimals,
+    })
+}
+
+/// Converts a decimal string such as `"1.5"` into base units using integer
+/// math only.
+pub fn ui_amount_to_raw(ui_amount: &str, decimals: u8) -> Result<u64, AppError> {
+    let invalid = || AppError::new(ErrorCode::InvalidAmount, format!("Invalid amount '{}'", ui_amount));
+
+    let (whole, fraction) = ui_amount.split_once('.').unwrap_or((ui_amount, ""));
+    if (whole.is_empty() && fraction.is_empty())
+        || !whole.bytes().all(|b| b.is_ascii_digit())
+        || !fraction.bytes().all(|b| b.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+    if fraction.len() > decimals as usize {
+        return Err(AppError::new(
+            ErrorCode::InvalidAmount,
+            format!("Amount has more than {} decimal places", decimals),
+        ));
+    }
+
+    let scale = pow10(decimals as usize)?;
+    let whole = parse_digits(whole)?;
+    let fraction = checked_mul_amount(
+        parse_digits(fraction)?,
+        pow10(decimals as usize - fraction.len())?,
+    )?;
+
+    checked_add_amount(checked_mul_amount(whole, scale)?, fraction)
+}
+
+fn pow10(exponent: usize) -> Result<u64, AppError> {
+    (0..exponent).try_fold(1u64, |acc, _| checked_mul_amount(acc, 10))
+}
+
+/// Parses an all-digit string (empty meaning zero), rejecting values past `u64::MAX`.
+fn parse_digits(digits: &str) -> Result<u64, AppError> {
+    digits.bytes().try_fold(0u64, |acc, b| {
+        checked_add_amount(checked_mul_amount(acc, 10)?, (b - b'0') as u64)
+    })
+}
+
+/// Formats base units as a decimal string, trimming trailing zeros.
+pub fn raw_amount_to_ui(amount: u64, decimals: u8) -> String {
+    let digits = format!("{:0>width$}", amount, width = decimals as usize + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - decimals as usize);
+    let fraction = fraction.trim_end_matches('0');
+
+    if fraction.is_empty() {
+        whole.to_string()
+    } else {
+        format!("{}.{}", whole, fraction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ui_amount_at_u64_max_converts() {
+        assert_eq!(ui_amount_to_raw("18446744073709551615", 0).unwrap(), u64::MAX);
+        assert_eq!(ui_amount_to_raw("18446744073.709551615", 9).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn ui_amount_past_u64_max_overflows() {
+        for (ui_amount, decimals) in [
+            ("18446744073709551616", 0),
+            ("18446744073.709551616", 9),
+            ("18446744074", 9),
+        ] {
+            let err = ui_amount_to_raw(ui_amount, decimals).unwrap_err();
+            assert_eq!(err.code, ErrorCode::AmountOverflow, "{ui_amount}");
+        }
+    }
+
+    #[test]
+    fn raw_amount_at_u64_max_round_trips() {
+        let ui_amount = raw_amount_to_ui(u64::MAX, 9);
+        assert_eq!(ui_amount, "18446744073.709551615");
+        assert_eq!(ui_amount_to_raw(&ui_amount, 9).unwrap(), u64::MAX);
+    }
+}
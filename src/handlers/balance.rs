@@ -0,0 +1,244 @@
+use axum::{extract::State, Json};
+use serde::{Deserialize, Serialize};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_program::pubkey::Pubkey;
+use spl_associated_token_account::{
+    get_associated_token_address, get_associated_token_address_with_program_id,
+};
+use spl_token_2022::{
+    extension::StateWithExtensions,
+    instruction::transfer_checked,
+    state::{Account as TokenAccount, Mint},
+};
+
+use crate::error::{AppError, ErrorCode};
+use crate::response::{ApiResponse, HandlerResult};
+use crate::rpc::{fetch_account, rpc_error, within};
+use crate::state::AppState;
+use crate::types::{
+    instruction_to_response, parse_mint, parse_pubkey, Encoding, InstructionType,
+    TokenInstructionResponse,
+};
+
+/// Wrapped SOL uses the same 9 decimals as native SOL.
+const NATIVE_MINT_DECIMALS: u8 = 9;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WrappedSolBalanceRequest {
+    pub owner: String,
+    /// Deadline for each RPC call, in milliseconds; clamped to
+    /// `MAX_RPC_TIMEOUT_MS`.
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenBalanceData {
+    pub owner: String,
+    pub mint: String,
+    pub account: String,
+    pub amount: u64,
+    pub decimals: u8,
+}
+
+/// Balance of the owner's native-mint ATA. A missing ATA reads as zero.
+pub async fn wrapped_sol_balance(
+    State(state): State<AppState>,
+    Json(payload): Json<WrappedSolBalanceRequest>,
+) -> HandlerResult<TokenBalanceData> {
+    let owner = parse_pubkey(&payload.owner, "owner")?;
+    let mint = spl_token::native_mint::id();
+    let account = get_associated_token_address(&owner, &mint);
+
+    let rpc = state.rpc()?;
+    let amount = within(payload.timeout_ms, fetch_token_amount(&rpc, &account))
+        .await??
+        .unwrap_or(0);
+
+    Ok(ApiResponse::from(TokenBalanceData {
+        owner: owner.to_string(),
+        mint: mint.to_string(),
+        account: account.to_string(),
+        amount,
+        decimals: NATIVE_MINT_DECIMALS,
+    }))
+}
+
+/// Reads a token account's raw amount, or `None` if the account doesn't exist.
+pub async fn fetch_token_amount(
+    client: &RpcClient,
+    account: &Pubkey,
+) -> Result<Option<u64>, AppError> {
+    Ok(fetch_token_account(client, account)
+        .await?
+        .map(|state| state.amount))
+}
+
+/// Reads and unpacks a token account, or `None` if the account doesn't exist.
+/// Token-2022 accounts are accepted; any extensions are ignored.
+pub async fn fetch_token_account(
+    client: &RpcClient,
+    account: &Pubkey,
+) -> Result<Option<TokenAccount>, AppError> {
+    let account = fetch_account(client, account, client.commitment())
+        .await
+        .map_err(rpc_error)?;
+
+    let Some(account) = account else {
+        return Ok(None);
+    };
+    let not_a_token_account =
+        || AppError::new(ErrorCode::InvalidAccount, "Account is not a token account");
+    // Other programs' accounts can happen to have the same layout.
+    if account.owner != spl_token::id() && account.owner != spl_token_2022::id() {
+        return Err(not_a_token_account());
+    }
+    let state = StateWithExtensions::<TokenAccount>::unpack(&account.data)
+        .map_err(|_| not_a_token_account())?;
+    Ok(Some(state.base))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DelegateInfoRequest {
+    pub account: String,
+    /// Deadline for each RPC call, in milliseconds; clamped to
+    /// `MAX_RPC_TIMEOUT_MS`.
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DelegateInfoData {
+    pub account: String,
+    /// `None` when no delegate is approved.
+    pub delegate: Option<String>,
+    pub delegated_amount: u64,
+}
+
+/// The approved delegate of a token account and its remaining allowance.
+pub async fn delegate_info(
+    State(state): State<AppState>,
+    Json(payload): Json<DelegateInfoRequest>,
+) -> HandlerResult<DelegateInfoData> {
+    let account = parse_pubkey(&payload.account, "account")?;
+
+    let rpc = state.rpc()?;
+    let token_account = within(payload.timeout_ms, fetch_token_account(&rpc, &account))
+        .await??
+        .ok_or_else(|| {
+            AppError::new(
+                ErrorCode::InvalidAccount,
+                format!("Token account {} not found", account),
+            )
+        })?;
+
+    Ok(ApiResponse::from(DelegateInfoData {
+        account: account.to_string(),
+        delegate: Option::<Pubkey>::from(token_account.delegate).map(|d| d.to_string()),
+        delegated_amount: token_account.delegated_amount,
+    }))
+}
+
+/// Reads a mint, returning the program that owns it and its decimals.
+pub async fn fetch_mint(client: &RpcClient, mint: &Pubkey) -> Result<(Pubkey, u8), AppError> {
+    let (token_program, state) = fetch_mint_state(client, mint).await?;
+    Ok((token_program, state.decimals))
+}
+
+/// Reads a mint, returning the program that owns it and its base state.
+pub async fn fetch_mint_state(
+    client: &RpcClient,
+    mint: &Pubkey,
+) -> Result<(Pubkey, Mint), AppError> {
+    let account = fetch_account(client, mint, client.commitment())
+        .await
+        .map_err(rpc_error)?
+        .ok_or_else(|| {
+            AppError::new(ErrorCode::InvalidAccount, format!("Mint {} not found", mint))
+        })?;
+
+    let not_a_mint = || AppError::new(ErrorCode::InvalidAccount, "Account is not a token mint");
+    if account.owner != spl_token::id() && account.owner != spl_token_2022::id() {
+        return Err(not_a_mint());
+    }
+    let state = StateWithExtensions::<Mint>::unpack(&account.data).map_err(|_| not_a_mint())?;
+    Ok((account.owner, state.base))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferAllRequest {
+    pub owner: String,
+    /// Destination token account, as for `/send/token`.
+    pub destination: String,
+    pub mint: String,
+    /// Deadline for each RPC call, in milliseconds; clamped to
+    /// `MAX_RPC_TIMEOUT_MS`.
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TransferAllData {
+    #[serde(flatten)]
+    pub instruction: TokenInstructionResponse,
+    pub source: String,
+    pub amount: u64,
+    pub decimals: u8,
+}
+
+/// "Send max": a `transfer_checked` of the owner's entire ATA balance, with
+/// the amount and decimals read on-chain.
+pub async fn transfer_all(
+    State(state): State<AppState>,
+    Json(payload): Json<TransferAllRequest>,
+) -> HandlerResult<TransferAllData> {
+    let owner = parse_pubkey(&payload.owner, "owner")?;
+    let destination = parse_pubkey(&payload.destination, "destination")?;
+    let mint = parse_mint(&payload.mint)?;
+    let rpc = state.rpc()?;
+
+    let (token_program, decimals) = within(payload.timeout_ms, fetch_mint(&rpc, &mint)).await??;
+    let source = get_associated_token_address_with_program_id(&owner, &mint, &token_program);
+    let amount = within(payload.timeout_ms, fetch_token_amount(&rpc, &source))
+        .await??
+        .ok_or_else(|| {
+            AppError::new(
+                ErrorCode::InvalidAccount,
+                format!("Token account {} not found", source),
+            )
+        })?;
+    if amount == 0 {
+        return Err(AppError::new(
+            ErrorCode::AmountZero,
+            format!("Token account {} has a zero balance", source),
+        ));
+    }
+
+    let ix = transfer_checked(
+        &token_program,
+        &source,
+        &mint,
+        &destination,
+        &owner,
+        &[],
+        amount,
+        decimals,
+    )
+    .map_err(|e| AppError::new(ErrorCode::InstructionBuildFailed, e.to_string()))?;
+
+    Ok(ApiResponse::from(TransferAllData {
+        instruction: instruction_to_response(
+            &ix,
+            InstructionType::SplTransferChecked,
+            Encoding::Base64,
+        ),
+        source: source.to_string(),
+        amount,
+        decimals,
+    }))
+}
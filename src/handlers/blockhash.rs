@@ -0,0 +1,54 @@
+use std::str::FromStr;
+
+use axum::{extract::State, Json};
+use serde::{Deserialize, Serialize};
+use solana_program::hash::Hash;
+
+use crate::error::{AppError, ErrorCode};
+use crate::response::{ApiResponse, HandlerResult};
+use crate::rpc::{rpc_error, within};
+use crate::state::AppState;
+use crate::types::Commitment;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockhashValidRequest {
+    pub blockhash: String,
+    /// Defaults to the RPC client's commitment (finalized).
+    #[serde(default)]
+    pub commitment: Option<Commitment>,
+    /// Deadline for each RPC call, in milliseconds; clamped to
+    /// `MAX_RPC_TIMEOUT_MS`.
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockhashValidData {
+    pub valid: bool,
+    pub blockhash: String,
+}
+
+/// Whether a cached blockhash can still be used in a new transaction.
+pub async fn is_blockhash_valid(
+    State(state): State<AppState>,
+    Json(payload): Json<BlockhashValidRequest>,
+) -> HandlerResult<BlockhashValidData> {
+    let blockhash = Hash::from_str(payload.blockhash.trim())
+        .map_err(|_| AppError::new(ErrorCode::InvalidBlockhash, "Invalid base58 blockhash"))?;
+
+    let rpc = state.rpc()?;
+    let commitment = payload
+        .commitment
+        .map(Into::into)
+        .unwrap_or_else(|| rpc.commitment());
+    let valid = within(payload.timeout_ms, rpc.is_blockhash_valid(&blockhash, commitment))
+        .await?
+        .map_err(rpc_error)?;
+
+    Ok(ApiResponse::from(BlockhashValidData {
+        valid,
+        blockhash: blockhash.to_string(),
+    }))
+}
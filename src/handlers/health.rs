@@ -0,0 +1,176 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use axum::{extract::State, http::StatusCode, Json};
+use serde::Serialize;
+use solana_client::client_error::{reqwest::Url, Result as ClientResult};
+use solana_sdk::signature::{Keypair, Signer};
+
+use crate::config::Config;
+use crate::response::{ApiResponse, HandlerResult};
+use crate::state::AppState;
+
+/// Upper bound for each individual check.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Ok,
+    Fail,
+    /// The subsystem isn't configured, so there was nothing to check.
+    Skipped,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CheckResult {
+    pub status: CheckStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Unlike `ApiResponse`, `success` here mirrors `healthy`, so a monitor that
+/// only checks `success` still sees a failing dependency.
+#[derive(Debug, Serialize)]
+pub struct DeepHealthReport {
+    pub success: bool,
+    pub data: DeepHealthData,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeepHealthData {
+    pub healthy: bool,
+    pub checks: BTreeMap<&'static str, CheckResult>,
+}
+
+/// Exercises RPC reachability, config loading and keypair generation/signing.
+/// Answers 503 when any check fails; a skipped check doesn't count as failing.
+pub async fn deep_health(
+    State(state): State<AppState>,
+) -> (StatusCode, Json<DeepHealthReport>) {
+    let rpc = state.rpc().ok();
+    let (rpc, config, keypair) = tokio::join!(
+        run_check(async move {
+            match rpc {
+                None => Ok(None),
+                Some(rpc) => rpc
+                    .get_version()
+                    .await
+                    .map(|version| Some(format!("solana-core {}", version.solana_core)))
+                    .map_err(|e| e.to_string()),
+            }
+        }),
+        run_check(async {
+            Config::load()
+                .map(|_| Some("valid".to_string()))
+                .map_err(|e| e.to_string())
+        }),
+        run_check(async { check_keypair().map(Some) }),
+    );
+
+    let checks = BTreeMap::from([("rpc", rpc), ("config", config), ("keypair", keypair)]);
+    let healthy = !checks
+        .values()
+        .any(|check| matches!(check.status, CheckStatus::Fail));
+    let status = if healthy {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (
+        status,
+        Json(DeepHealthReport {
+            success: healthy,
+            data: DeepHealthData { healthy, checks },
+        }),
+    )
+}
+
+/// Runs one check on its own task so a panic is reported as a failure rather
+/// than taking the request down, and bounds it with `CHECK_TIMEOUT`.
+/// `Ok(None)` from the check means it was skipped.
+async fn run_check<F>(check: F) -> CheckResult
+where
+    F: Future<Output = Result<Option<String>, String>> + Send + 'static,
+{
+    let outcome = tokio::time::timeout(CHECK_TIMEOUT, tokio::spawn(check)).await;
+    let (status, detail) = match outcome {
+        Err(_) => (CheckStatus::Fail, Some("timed out".to_string())),
+        Ok(Err(_)) => (CheckStatus::Fail, Some("check panicked".to_string())),
+        Ok(Ok(Err(e))) => (CheckStatus::Fail, Some(e)),
+        Ok(Ok(Ok(None))) => (CheckStatus::Skipped, None),
+        Ok(Ok(Ok(Some(detail)))) => (CheckStatus::Ok, Some(detail)),
+    };
+    CheckResult { status, detail }
+}
+
+fn check_keypair() -> Result<String, String> {
+    let keypair = Keypair::new();
+    let message = b"health check";
+    let signature = keypair.sign_message(message);
+    if signature.verify(keypair.pubkey().as_ref(), message) {
+        Ok("generated, signed and verified".to_string())
+    } else {
+        Err("signature did not verify".to_string())
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcLatencyData {
+    /// Origin of the RPC endpoint in use; the rest may hold credentials.
+    pub url: String,
+    pub calls: BTreeMap<&'static str, CallLatency>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallLatency {
+    pub status: CheckStatus,
+    /// Time until the call answered, failed or hit `CHECK_TIMEOUT`.
+    pub latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Times a few representative RPC calls against the configured node, to help
+/// decide whether a provider is too slow. A failing call is reported in its
+/// entry rather than failing the request.
+pub async fn rpc_latency(State(state): State<AppState>) -> HandlerResult<RpcLatencyData> {
+    let rpc = state.rpc()?;
+    let (slot, blockhash) = tokio::join!(
+        time_call(rpc.get_slot()),
+        time_call(rpc.get_latest_blockhash()),
+    );
+
+    Ok(ApiResponse::from(RpcLatencyData {
+        url: redacted_url(&rpc.url()),
+        calls: BTreeMap::from([("getSlot", slot), ("getLatestBlockhash", blockhash)]),
+    }))
+}
+
+async fn time_call<T>(call: impl Future<Output = ClientResult<T>>) -> CallLatency {
+    let started = Instant::now();
+    let outcome = tokio::time::timeout(CHECK_TIMEOUT, call).await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+    let (status, detail) = match outcome {
+        Err(_) => (CheckStatus::Fail, Some("timed out".to_string())),
+        Ok(Err(e)) => (CheckStatus::Fail, Some(e.to_string())),
+        Ok(Ok(_)) => (CheckStatus::Ok, None),
+    };
+    CallLatency {
+        status,
+        latency_ms,
+        detail,
+    }
+}
+
+/// Providers put API keys in the path, query or userinfo, so only the
+/// origin is shown.
+fn redacted_url(url: &str) -> String {
+    Url::parse(url)
+        .map(|url| url.origin().ascii_serialization())
+        .unwrap_or_else(|_| "<unparseable>".to_string())
+}
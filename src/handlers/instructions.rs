@@ -0,0 +1,121 @@
+use axum::{
+    extract::State,
+    response::{IntoResponse, Response},
+    Json,
+};
+use futures_util::stream;
+use serde::{Deserialize, Serialize};
+
+use solana_program::instruction::Instruction;
+
+use super::transfer::{
+    build_send_sol, build_send_token, send_sol_instruction, send_token_instructions,
+    SendSolRequest, SendTokenData, SendTokenRequest, SolInstructionResponse,
+};
+use crate::error::AppError;
+use crate::ndjson::ndjson_response;
+use crate::proto::ResponseFormat;
+use crate::response::ApiResponse;
+use crate::state::AppState;
+use crate::types::{
+    ensure_batch_size, instruction_to_response, parse_pubkey, Encoding, InstructionType,
+    TokenInstructionResponse,
+};
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoRequest {
+    pub memo: String,
+    #[serde(default)]
+    pub signers: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum InstructionSpec {
+    SendSol(SendSolRequest),
+    SendToken(SendTokenRequest),
+    Memo(MemoRequest),
+}
+
+/// Generous upper bound on one serialized `InstructionSpec`, for the route's
+/// body limit; a memo is the largest, and must fit in a transaction anyway.
+pub const MAX_INSTRUCTION_SPEC_BYTES: usize = 2 * 1024;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchInstructionsRequest {
+    pub instructions: Vec<InstructionSpec>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum InstructionResult {
+    Sol(SolInstructionResponse),
+    SendToken(SendTokenData),
+    Token(TokenInstructionResponse),
+}
+
+/// Builds each requested instruction in order, using the same builders as the
+/// single-instruction endpoints. The first invalid entry fails the whole batch.
+///
+/// With `Accept: application/x-ndjson` results are streamed one per line; an
+/// invalid entry then ends the stream with an error line instead of a 400.
+pub async fn batch_instructions(
+    State(_): State<AppState>,
+    format: ResponseFormat,
+    Json(payload): Json<BatchInstructionsRequest>,
+) -> Result<Response, AppError> {
+    ensure_batch_size(payload.instructions.len())?;
+
+    let results = payload
+        .instructions
+        .into_iter()
+        .enumerate()
+        .map(|(index, spec)| {
+            build_instruction(&spec).map_err(|mut e| {
+                e.message = format!("instructions[{}]: {}", index, e.message);
+                e
+            })
+        });
+
+    if format == ResponseFormat::Ndjson {
+        return Ok(ndjson_response(stream::iter(results)));
+    }
+
+    Ok(ApiResponse::from(results.collect::<Result<Vec<_>, _>>()?).into_response())
+}
+
+pub fn build_instruction(spec: &InstructionSpec) -> Result<InstructionResult, AppError> {
+    match spec {
+        InstructionSpec::SendSol(req) => build_send_sol(req).map(InstructionResult::Sol),
+        InstructionSpec::SendToken(req) => build_send_token(req).map(InstructionResult::SendToken),
+        InstructionSpec::Memo(req) => build_memo(req).map(InstructionResult::Token),
+    }
+}
+
+/// The bare instructions for `spec`, for composing into a transaction. Only a
+/// `sendToken` with `createDestinationAta` yields more than one.
+pub fn spec_instructions(spec: &InstructionSpec) -> Result<Vec<Instruction>, AppError> {
+    match spec {
+        InstructionSpec::SendSol(req) => send_sol_instruction(req).map(|ix| vec![ix]),
+        InstructionSpec::SendToken(req) => send_token_instructions(req),
+        InstructionSpec::Memo(req) => memo_instruction(req).map(|ix| vec![ix]),
+    }
+}
+
+fn build_memo(payload: &MemoRequest) -> Result<TokenInstructionResponse, AppError> {
+    let ix = memo_instruction(payload)?;
+    Ok(instruction_to_response(&ix, InstructionType::Memo, Encoding::Base64))
+}
+
+fn memo_instruction(payload: &MemoRequest) -> Result<Instruction, AppError> {
+    let signers = payload
+        .signers
+        .iter()
+        .map(|s| parse_pubkey(s, "signer"))
+        .collect::<Result<Vec<_>, _>>()?;
+    let signer_refs: Vec<_> = signers.iter().collect();
+
+    Ok(spl_memo::build_memo(payload.memo.as_bytes(), &signer_refs))
+}
@@ -1,7 +1,28 @@
-use axum::Json;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use axum::{
+    extract::{Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+    Json,
+};
+use bip39::{Language, Mnemonic, Seed};
 use bs58;
-use serde::Serialize;
-use solana_sdk::signature::{Keypair, Signer};
+use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    derivation_path::DerivationPath,
+    signature::{keypair_from_seed, keypair_from_seed_and_derivation_path, Keypair, Signer},
+};
+use tokio::sync::OwnedSemaphorePermit;
+
+use crate::config;
+use crate::error::{AppError, ErrorCode};
+use crate::response::{ApiResponse, HandlerResult};
+use crate::state::AppState;
+use crate::types::{decode_secret, ensure_batch_size, parse_pubkey};
 
 #[derive(Serialize)]
 pub struct KeypairData {
@@ -9,19 +30,492 @@ pub struct KeypairData {
     pub secret: String,
 }
 
-#[derive(Serialize)]
-pub struct ApiResponse<T> {
-    pub success: bool,
-    pub data: T,
+/// `?download=true` sends the keypair as a `keypair.json` attachment in the
+/// Solana CLI keyfile format: a JSON array of the 64 secret-key bytes.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadQuery {
+    #[serde(default)]
+    pub download: bool,
 }
 
-pub async fn generate_keypair() -> Json<ApiResponse<KeypairData>> {
+pub async fn generate_keypair(
+    State(_): State<AppState>,
+    Query(query): Query<DownloadQuery>,
+) -> Response {
     let keypair = Keypair::new();
+    if query.download {
+        return keyfile_download(&keypair);
+    }
+
+    let pubkey = keypair.pubkey().to_string();
+    let secret = bs58::encode(keypair.to_bytes()).into_string();
+
+    ApiResponse::from(KeypairData { pubkey, secret }).into_response()
+}
+
+#[derive(Serialize)]
+pub struct PubkeyData {
+    pub pubkey: String,
+}
+
+/// A fresh random pubkey, for callers that need a throwaway address but not
+/// its secret. The keypair is dropped without the secret leaving the server.
+pub async fn generate_pubkey(State(_): State<AppState>) -> ApiResponse<PubkeyData> {
+    ApiResponse::from(PubkeyData {
+        pubkey: Keypair::new().pubkey().to_string(),
+    })
+}
+
+fn keyfile_download(keypair: &Keypair) -> Response {
+    (
+        [(header::CONTENT_DISPOSITION, "attachment; filename=\"keypair.json\"")],
+        Json(keypair.to_bytes().to_vec()),
+    )
+        .into_response()
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeterministicKeypairRequest {
+    pub seed: u64,
+}
+
+/// Derives a keypair from a ChaCha20 RNG seeded with `seed`, so the same seed
+/// always yields the same keypair.
+///
+/// FOR TESTS ONLY. Anyone who knows or guesses the seed can recreate the
+/// secret key; never hold real funds with a key from this endpoint.
+pub async fn generate_deterministic_keypair(
+    State(_): State<AppState>,
+    Json(payload): Json<DeterministicKeypairRequest>,
+) -> ApiResponse<KeypairData> {
+    let mut rng = ChaCha20Rng::seed_from_u64(payload.seed);
+    let keypair = Keypair::generate(&mut rng);
+    let pubkey = keypair.pubkey().to_string();
+    let secret = bs58::encode(keypair.to_bytes()).into_string();
+
+    ApiResponse::from(KeypairData { pubkey, secret })
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidateKeypairRequest {
+    pub secret: String,
+    pub pubkey: String,
+}
+
+#[derive(Serialize)]
+pub struct ValidateKeypairData {
+    pub valid: bool,
+}
+
+/// Checks that `secret` derives the expected `pubkey`.
+pub async fn validate_keypair(
+    State(_): State<AppState>,
+    Json(payload): Json<ValidateKeypairRequest>,
+) -> HandlerResult<ValidateKeypairData> {
+    let secret_bytes = decode_secret(&payload.secret)?;
+    let keypair = Keypair::from_bytes(&secret_bytes).map_err(|_| {
+        AppError::new(ErrorCode::InvalidSecret, "Failed to parse secret key into Keypair")
+    })?;
+    let expected = parse_pubkey(&payload.pubkey, "pubkey")?;
+
+    Ok(ApiResponse::from(ValidateKeypairData {
+        valid: keypair.pubkey() == expected,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoverFromBytesRequest {
+    /// The secret as a JSON byte array, as in a Solana CLI keypair file.
+    pub secret: Vec<u8>,
+}
+
+/// Rebuilds a keypair from its 64 raw bytes and returns the base58 forms.
+pub async fn recover_from_bytes(
+    State(_): State<AppState>,
+    Json(payload): Json<RecoverFromBytesRequest>,
+) -> HandlerResult<KeypairData> {
+    if payload.secret.len() != 64 {
+        return Err(AppError::new(
+            ErrorCode::InvalidSecret,
+            format!(
+                "Invalid secret key: expected 64 bytes, got {}",
+                payload.secret.len()
+            ),
+        ));
+    }
+    let keypair = Keypair::from_bytes(&payload.secret).map_err(|_| {
+        AppError::new(ErrorCode::InvalidSecret, "Failed to parse secret key into Keypair")
+    })?;
     let pubkey = keypair.pubkey().to_string();
     let secret = bs58::encode(keypair.to_bytes()).into_string();
 
-    Json(ApiResponse {
-        success: true,
-        data: KeypairData { pubkey, secret },
+    Ok(ApiResponse::from(KeypairData { pubkey, secret }))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchWithSeedsRequest {
+    pub mnemonic: String,
+    /// Optional BIP39 passphrase ("25th word").
+    #[serde(default)]
+    pub passphrase: String,
+    pub count: usize,
+}
+
+#[derive(Serialize)]
+pub struct DerivedKeypairData {
+    pub index: u32,
+    pub pubkey: String,
+    pub secret: String,
+}
+
+/// Derives the first `count` wallet accounts of a mnemonic, at
+/// `m/44'/501'/{index}'/0'` as Phantom and the Solana CLI do.
+pub async fn batch_with_seeds(
+    State(_): State<AppState>,
+    Json(payload): Json<BatchWithSeedsRequest>,
+) -> HandlerResult<Vec<DerivedKeypairData>> {
+    ensure_batch_size(payload.count)?;
+    let seed = mnemonic_seed(&payload.mnemonic, &payload.passphrase)?;
+
+    let accounts = (0..payload.count as u32)
+        .map(|index| {
+            let keypair = derive_keypair(&seed, index)?;
+            Ok(DerivedKeypairData {
+                index,
+                pubkey: keypair.pubkey().to_string(),
+                secret: bs58::encode(keypair.to_bytes()).into_string(),
+            })
+        })
+        .collect::<Result<Vec<_>, AppError>>()?;
+
+    Ok(ApiResponse::from(accounts))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FromMnemonicRequest {
+    pub mnemonic: String,
+    /// Optional BIP39 passphrase ("25th word").
+    #[serde(default)]
+    pub passphrase: String,
+    /// Wallet account index; the first account (0) by default.
+    #[serde(default)]
+    pub account: u32,
+}
+
+/// Recovers one wallet account from a mnemonic of any standard length, at
+/// `m/44'/501'/{account}'/0'`.
+pub async fn from_mnemonic(
+    State(_): State<AppState>,
+    Json(payload): Json<FromMnemonicRequest>,
+) -> HandlerResult<KeypairData> {
+    let seed = mnemonic_seed(&payload.mnemonic, &payload.passphrase)?;
+    let keypair = derive_keypair(&seed, payload.account)?;
+
+    Ok(ApiResponse::from(KeypairData {
+        pubkey: keypair.pubkey().to_string(),
+        secret: bs58::encode(keypair.to_bytes()).into_string(),
+    }))
+}
+
+/// Word counts of the standard BIP39 entropy lengths, 128 to 256 bits.
+const MNEMONIC_WORD_COUNTS: [usize; 5] = [12, 15, 18, 21, 24];
+
+/// Validates an English BIP39 mnemonic (word count, words and checksum) and
+/// returns its 64-byte seed. The count and words are checked first, so a
+/// mistyped phrase gets a specific error rather than a checksum failure.
+pub fn mnemonic_seed(phrase: &str, passphrase: &str) -> Result<Vec<u8>, AppError> {
+    let words: Vec<&str> = phrase.split_whitespace().collect();
+    if !MNEMONIC_WORD_COUNTS.contains(&words.len()) {
+        return Err(AppError::new(
+            ErrorCode::InvalidMnemonic,
+            format!("Mnemonic has {} words; expected 12, 15, 18, 21 or 24", words.len()),
+        ));
+    }
+    let wordmap = Language::English.wordmap();
+    if let Some((i, word)) = words
+        .iter()
+        .enumerate()
+        .find(|(_, word)| wordmap.get_bits(word).is_err())
+    {
+        return Err(AppError::new(
+            ErrorCode::InvalidMnemonic,
+            format!("Word {} ({:?}) is not in the English BIP39 wordlist", i + 1, word),
+        ));
+    }
+
+    let mnemonic = Mnemonic::from_phrase(&words.join(" "), Language::English).map_err(|e| {
+        AppError::new(ErrorCode::InvalidMnemonic, format!("Invalid mnemonic: {}", e))
+    })?;
+    Ok(Seed::new(&mnemonic, passphrase).as_bytes().to_vec())
+}
+
+/// The keypair for wallet account `account`, at `m/44'/501'/{account}'/0'`.
+pub fn derive_keypair(seed: &[u8], account: u32) -> Result<Keypair, AppError> {
+    let path = DerivationPath::new_bip44(Some(account), Some(0));
+    keypair_from_seed_and_derivation_path(seed, Some(path)).map_err(|e| {
+        AppError::new(ErrorCode::InternalError, format!("Key derivation failed: {}", e))
     })
 }
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FromPhantomRequest {
+    pub secret: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PhantomSecretFormat {
+    /// 64 bytes: the 32-byte seed followed by the public key (Phantom's export).
+    Keypair,
+    /// 32 bytes: the seed alone, as some other wallets export it.
+    Seed,
+}
+
+#[derive(Serialize)]
+pub struct FromPhantomData {
+    pub pubkey: String,
+    /// Always the standard 64-byte base58 form, whatever was supplied.
+    pub secret: String,
+    pub format: PhantomSecretFormat,
+}
+
+/// Imports a wallet-exported base58 private key, accepting the full 64-byte
+/// keypair or a 32-byte seed, and says which was detected.
+pub async fn from_phantom(
+    State(_): State<AppState>,
+    Json(payload): Json<FromPhantomRequest>,
+) -> HandlerResult<FromPhantomData> {
+    let invalid = |message: String| AppError::new(ErrorCode::InvalidSecret, message);
+    let secret = payload.secret.trim();
+
+    if secret.starts_with('[') {
+        return Err(invalid(
+            "Detected a JSON byte array (Solana CLI keypair file), not a base58 export; \
+             use /keypair/recover-from-bytes"
+                .to_string(),
+        ));
+    }
+    let bytes = bs58::decode(secret).into_vec().map_err(|e| {
+        invalid(format!("Not a base58 string ({}); Phantom exports base58", e))
+    })?;
+
+    let (keypair, format) = match bytes.len() {
+        64 => {
+            let keypair = keypair_from_seed(&bytes[..32])
+                .map_err(|e| invalid(format!("Detected a 64-byte keypair but {}", e)))?;
+            if keypair.pubkey().as_ref() != &bytes[32..] {
+                return Err(invalid(
+                    "Detected a 64-byte keypair, but its last 32 bytes are not the public key \
+                     of its first 32; the export is corrupted or truncated"
+                        .to_string(),
+                ));
+            }
+            (keypair, PhantomSecretFormat::Keypair)
+        }
+        32 => {
+            let keypair = keypair_from_seed(&bytes)
+                .map_err(|e| invalid(format!("Detected a 32-byte seed but {}", e)))?;
+            (keypair, PhantomSecretFormat::Seed)
+        }
+        len => {
+            return Err(invalid(format!(
+                "Decoded {} bytes; expected 64 (full keypair) or 32 (seed only)",
+                len
+            )))
+        }
+    };
+
+    Ok(ApiResponse::from(FromPhantomData {
+        pubkey: keypair.pubkey().to_string(),
+        secret: bs58::encode(keypair.to_bytes()).into_string(),
+        format,
+    }))
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GrindMode {
+    #[default]
+    Prefix,
+    Suffix,
+    Contains,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GrindRequest {
+    /// Base58 characters to look for in the pubkey; matching is case-sensitive.
+    pub pattern: String,
+    #[serde(default)]
+    pub mode: GrindMode,
+}
+
+#[derive(Serialize)]
+pub struct GrindData {
+    pub found: bool,
+    /// Present only when `found` is true.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pubkey: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    /// Keypairs generated across all threads before stopping.
+    pub attempts: u64,
+}
+
+const BASE58_ALPHABET: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Generates random keypairs on `GRIND_THREADS` blocking threads until one's
+/// pubkey matches, stopping at `GRIND_MAX_ATTEMPTS` or `GRIND_TIMEOUT_SECS`,
+/// whichever comes first. Running out is not an error: `found` is false.
+/// At most `GRIND_MAX_CONCURRENT` run at once; past that, a 503.
+pub async fn grind(
+    State(state): State<AppState>,
+    Json(payload): Json<GrindRequest>,
+) -> HandlerResult<GrindData> {
+    let pattern = payload.pattern.trim().to_string();
+    if pattern.is_empty() {
+        return Err(AppError::new(ErrorCode::InvalidPattern, "pattern must not be empty"));
+    }
+    if let Some(c) = pattern.chars().find(|c| !BASE58_ALPHABET.contains(*c)) {
+        return Err(AppError::new(
+            ErrorCode::InvalidPattern,
+            format!("{:?} is not a base58 character, so no pubkey can match", c),
+        ));
+    }
+
+    let config = config::get();
+    // Each grind already occupies every grind thread, so a busy server
+    // refuses more rather than queueing blocking work behind it.
+    let permit = state.grinds.clone().try_acquire_owned().map_err(|_| {
+        let mut error = AppError::new(
+            ErrorCode::ServerBusy,
+            "Too many grinds in progress; retry shortly",
+        );
+        error.retry_after = Some(config.grind_timeout.as_secs().to_string());
+        error
+    })?;
+    let search = Arc::new(GrindSearch {
+        pattern,
+        mode: payload.mode,
+        max_attempts: config.grind_max_attempts,
+        deadline: Instant::now() + config.grind_timeout,
+        attempts: AtomicU64::new(0),
+        done: AtomicBool::new(false),
+        _permit: permit,
+    });
+
+    let workers: Vec<_> = (0..config.grind_threads)
+        .map(|_| {
+            let search = search.clone();
+            tokio::task::spawn_blocking(move || search.run())
+        })
+        .collect();
+    let mut found = None;
+    for worker in workers {
+        let keypair = worker.await.map_err(|e| {
+            AppError::new(ErrorCode::InternalError, format!("Grind worker failed: {}", e))
+        })?;
+        found = found.or(keypair);
+    }
+
+    Ok(ApiResponse::from(GrindData {
+        found: found.is_some(),
+        pubkey: found.as_ref().map(|keypair| keypair.pubkey().to_string()),
+        secret: found
+            .as_ref()
+            .map(|keypair| bs58::encode(keypair.to_bytes()).into_string()),
+        attempts: search.attempts.load(Ordering::Relaxed),
+    }))
+}
+
+/// State shared by the grind workers; the first match sets `done` for all.
+struct GrindSearch {
+    pattern: String,
+    mode: GrindMode,
+    max_attempts: u64,
+    deadline: Instant,
+    attempts: AtomicU64,
+    done: AtomicBool,
+    /// Released once the last worker is done, even if the request was
+    /// dropped first.
+    _permit: OwnedSemaphorePermit,
+}
+
+impl GrindSearch {
+    fn run(&self) -> Option<Keypair> {
+        while !self.done.load(Ordering::Relaxed) {
+            let attempt = self.attempts.fetch_add(1, Ordering::Relaxed);
+            if self.exhausted(attempt) {
+                self.attempts.fetch_sub(1, Ordering::Relaxed);
+                self.done.store(true, Ordering::Relaxed);
+                break;
+            }
+            let keypair = Keypair::new();
+            if self.matches(&keypair.pubkey().to_string()) {
+                self.done.store(true, Ordering::Relaxed);
+                return Some(keypair);
+            }
+        }
+        None
+    }
+
+    fn exhausted(&self, attempt: u64) -> bool {
+        // Reading the clock every attempt would dominate the loop.
+        attempt >= self.max_attempts
+            || (attempt.is_multiple_of(1024) && Instant::now() > self.deadline)
+    }
+
+    fn matches(&self, pubkey: &str) -> bool {
+        match self.mode {
+            GrindMode::Prefix => pubkey.starts_with(&self.pattern),
+            GrindMode::Suffix => pubkey.ends_with(&self.pattern),
+            GrindMode::Contains => pubkey.contains(&self.pattern),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn grind_refuses_when_every_permit_is_taken() {
+        let state = AppState::for_tests();
+        let permits = config::get().grind_max_concurrent as u32;
+        let _held = state.grinds.clone().try_acquire_many_owned(permits).unwrap();
+        let request: GrindRequest =
+            serde_json::from_value(serde_json::json!({ "pattern": "a" })).unwrap();
+
+        let Err(err) = grind(State(state), Json(request)).await else {
+            panic!("expected the grind to be refused");
+        };
+        assert_eq!(err.code, ErrorCode::ServerBusy);
+        assert_eq!(err.code.status(), axum::http::StatusCode::SERVICE_UNAVAILABLE);
+        assert!(err.retry_after.is_some());
+    }
+
+    async fn deterministic(seed: u64) -> KeypairData {
+        let request = DeterministicKeypairRequest { seed };
+        generate_deterministic_keypair(State(AppState::for_tests()), Json(request))
+            .await
+            .into_data()
+    }
+
+    #[tokio::test]
+    async fn deterministic_keypair_repeats_for_a_seed() {
+        let first = deterministic(42).await;
+        let second = deterministic(42).await;
+        assert_eq!(first.pubkey, second.pubkey);
+        assert_eq!(first.secret, second.secret);
+
+        let other = deterministic(43).await;
+        assert_ne!(first.pubkey, other.pubkey);
+    }
+}
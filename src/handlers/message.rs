@@ -1,8 +1,23 @@
-use axum::Json;
-use base64;
+use axum::{extract::State, Json};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
 use bs58;
 use ed25519_dalek::{Keypair, PublicKey, Signature, Signer, Verifier};
 use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use sha3::{Digest, Keccak256};
+use solana_sdk::{
+    message::Message,
+    signature::{Keypair as SolanaKeypair, Signer as _},
+};
+use tracing::info_span;
+
+use crate::config;
+use crate::error::{AppError, ErrorCode};
+use crate::response::{ApiResponse, HandlerResult};
+use crate::state::AppState;
+use crate::keys::signing_secret;
+use crate::types::{ensure_batch_size, Encoding, MessageEncoding};
 
 /// ------------------ /message/sign ------------------
 
@@ -10,62 +25,67 @@ use serde::{Deserialize, Serialize};
 #[serde(rename_all = "camelCase")]
 pub struct SignMessageRequest {
     pub message: String,
-    pub secret: String,
+    /// Base58 secret key; give either this or `keyAlias`.
+    #[serde(default)]
+    pub secret: Option<String>,
+    /// Name of a server-held key (`SERVER_KEYS`) to sign with instead.
+    #[serde(default)]
+    pub key_alias: Option<String>,
+    /// Encoding of the returned `signature`; base64 by default.
+    #[serde(default)]
+    pub encoding: Encoding,
+    /// How to read `message`: `utf8` (default), or `base64`/`hex` to sign
+    /// arbitrary bytes.
+    #[serde(default)]
+    pub message_encoding: MessageEncoding,
 }
 
 #[derive(Debug, Serialize)]
 pub struct SignMessageResponse {
     pub signature: String,
     pub public_key: String,
+    /// The same 32 public-key bytes as `public_key`, base64-encoded.
+    #[serde(rename = "publicKeyBase64")]
+    pub public_key_base64: String,
     pub message: String,
 }
 
-#[derive(Debug, Serialize)]
-pub struct ApiResponse<T> {
-    pub success: bool,
-    pub data: T,
-}
-
-pub async fn sign_message(Json(payload): Json<SignMessageRequest>) -> Json<ApiResponse<SignMessageResponse>> {
-    let secret_bytes = match bs58::decode(&payload.secret).into_vec() {
-        Ok(bytes) if bytes.len() == 64 => bytes,
-        _ => {
-            return Json(ApiResponse {
-                success: false,
-                data: SignMessageResponse {
-                    signature: "".into(),
-                    public_key: "".into(),
-                    message: "Invalid or malformed secret key (expected 64-byte base58)".into(),
-                },
-            })
-        }
-    };
+pub async fn sign_message(
+    State(_): State<AppState>,
+    Json(payload): Json<SignMessageRequest>,
+) -> HandlerResult<SignMessageResponse> {
+    let message_bytes = payload.message_encoding.decode(&payload.message)?;
+    ensure_message_size(message_bytes.len())?;
 
-    let keypair = match Keypair::from_bytes(&secret_bytes) {
-        Ok(kp) => kp,
-        Err(_) => {
-            return Json(ApiResponse {
-                success: false,
-                data: SignMessageResponse {
-                    signature: "".into(),
-                    public_key: "".into(),
-                    message: "Failed to parse secret key into Keypair".into(),
-                },
-            })
-        }
-    };
+    let secret_bytes = signing_secret(payload.secret.as_deref(), payload.key_alias.as_deref())?;
 
-    let message_bytes = payload.message.as_bytes();
-    let signature = keypair.sign(message_bytes);
+    let keypair = Keypair::from_bytes(&secret_bytes).map_err(|_| {
+        AppError::new(ErrorCode::InvalidSecret, "Failed to parse secret key into Keypair")
+    })?;
+    let public_key = bs58::encode(keypair.public).into_string();
+    // Only the derived public key: `secret` must never reach a span or event.
+    let _span = info_span!("sign_message", pubkey = %public_key).entered();
 
-    Json(ApiResponse {
-        success: true,
-        data: SignMessageResponse {
-            signature: base64::encode(signature.to_bytes()),
-            public_key: bs58::encode(keypair.public).into_string(),
-            message: payload.message,
-        },
-    })
+    let signature = keypair.sign(&message_bytes);
+
+    Ok(ApiResponse::from(SignMessageResponse {
+        signature: payload.encoding.encode(&signature.to_bytes()),
+        public_key,
+        public_key_base64: STANDARD.encode(keypair.public),
+        message: payload.message,
+    }))
+}
+
+/// Rejects messages over `MAX_MESSAGE_BYTES` before any signing work.
+fn ensure_message_size(len: usize) -> Result<(), AppError> {
+    let max = config::get().max_message_bytes;
+    if len > max {
+        return Err(AppError::new(
+            ErrorCode::MessageTooLarge,
+            format!("Message is {} bytes; the limit is {} bytes", len, max),
+        ));
+    }
+    Ok(())
 }
 
 /// ------------------ /message/verify ------------------
@@ -76,81 +96,385 @@ pub struct VerifyMessageRequest {
     pub message: String,
     pub signature: String,
     pub pubkey: String,
+    /// Encoding of `signature`; base64 by default.
+    #[serde(default)]
+    pub encoding: Encoding,
+    /// How to read `message`, as for `/message/sign`: `utf8` (default), or
+    /// `base64`/`hex` for signatures over arbitrary bytes.
+    #[serde(default)]
+    pub message_encoding: MessageEncoding,
+    /// Also accept signatures that only pass the lenient (non-strict) check.
+    /// Off by default; meant for legacy signers.
+    #[serde(default)]
+    pub allow_malleable: bool,
 }
 
 #[derive(Debug, Serialize)]
 pub struct VerifyMessageData {
     pub valid: bool,
+    /// Whether strict verification was in force, i.e. `allowMalleable` was
+    /// not set.
+    pub strict: bool,
+    /// Why verification failed; omitted when `valid` is true.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<&'static str>,
+    /// Set when the signature was accepted only by the lenient check.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning: Option<&'static str>,
     pub message: String,
     pub pubkey: String,
 }
 
-pub async fn verify_message(Json(payload): Json<VerifyMessageRequest>) -> Json<ApiResponse<VerifyMessageData>> {
-    let pubkey_bytes = match bs58::decode(&payload.pubkey).into_vec() {
-        Ok(bytes) => bytes,
-        Err(_) => {
-            return Json(ApiResponse {
-                success: false,
-                data: VerifyMessageData {
-                    valid: false,
-                    message: "Invalid base58 pubkey".into(),
-                    pubkey: payload.pubkey,
-                },
-            })
-        }
-    };
+const MALLEABLE_WARNING: &str = "accepted by non-strict verification only: the signature is \
+    non-canonical or the key is weak, so it may not be unique to this signer and message; \
+    do not use it as an identifier or for replay protection";
 
-    let public_key = match PublicKey::from_bytes(&pubkey_bytes) {
-        Ok(pk) => pk,
-        Err(_) => {
-            return Json(ApiResponse {
-                success: false,
-                data: VerifyMessageData {
-                    valid: false,
-                    message: "Failed to parse pubkey".into(),
-                    pubkey: payload.pubkey,
-                },
-            })
-        }
-    };
+pub async fn verify_message(
+    State(_): State<AppState>,
+    Json(payload): Json<VerifyMessageRequest>,
+) -> HandlerResult<VerifyMessageData> {
+    let public_key = decode_public_key(&payload.pubkey)?;
+    let signature = decode_signature(&payload.signature, payload.encoding)?;
+    let message_bytes = payload.message_encoding.decode(&payload.message)?;
+    let _span = info_span!("verify_message", pubkey = %payload.pubkey.trim()).entered();
 
-    let signature_bytes = match base64::decode(&payload.signature) {
-        Ok(sig) => sig,
-        Err(_) => {
-            return Json(ApiResponse {
-                success: false,
-                data: VerifyMessageData {
-                    valid: false,
-                    message: "Invalid base64 signature".into(),
-                    pubkey: payload.pubkey,
-                },
-            })
-        }
+    let strict_ok = public_key.verify_strict(&message_bytes, &signature).is_ok();
+    let lenient_ok = strict_ok || public_key.verify(&message_bytes, &signature).is_ok();
+
+    // Undecodable input is already a 400 above. Here the input parsed, so a
+    // failure is either a plain mismatch or a signature that only passes the
+    // lenient check (non-canonical or weak-key), which strict mode rejects.
+    let (valid, reason, warning) = match (strict_ok, lenient_ok) {
+        (true, _) => (true, None, None),
+        (false, true) if payload.allow_malleable => (true, None, Some(MALLEABLE_WARNING)),
+        (false, true) => (
+            false,
+            Some("malformed input: signature is non-canonical or uses a weak key"),
+            None,
+        ),
+        (false, false) => (false, Some("signature does not match"), None),
     };
 
-    let signature = match Signature::from_bytes(&signature_bytes) {
-        Ok(sig) => sig,
-        Err(_) => {
-            return Json(ApiResponse {
-                success: false,
-                data: VerifyMessageData {
-                    valid: false,
-                    message: "Failed to parse signature".into(),
-                    pubkey: payload.pubkey,
-                },
-            })
+    Ok(ApiResponse::from(VerifyMessageData {
+        valid,
+        strict: !payload.allow_malleable,
+        reason,
+        warning,
+        message: payload.message,
+        pubkey: payload.pubkey,
+    }))
+}
+
+fn decode_public_key(pubkey: &str) -> Result<PublicKey, AppError> {
+    let pubkey_bytes = bs58::decode(pubkey.trim())
+        .into_vec()
+        .map_err(|_| AppError::new(ErrorCode::InvalidPubkey, "Invalid base58 pubkey"))?;
+
+    PublicKey::from_bytes(&pubkey_bytes)
+        .map_err(|_| AppError::new(ErrorCode::InvalidPubkey, "Failed to parse pubkey"))
+}
+
+fn decode_signature(signature: &str, encoding: Encoding) -> Result<Signature, AppError> {
+    let signature_bytes = encoding.decode(signature).map_err(|_| {
+        AppError::new(
+            ErrorCode::InvalidSignature,
+            format!("Invalid {} signature", encoding.name()),
+        )
+    })?;
+
+    Signature::from_bytes(&signature_bytes)
+        .map_err(|_| AppError::new(ErrorCode::InvalidSignature, "Failed to parse signature"))
+}
+
+/// ------------------ /message/verify-any ------------------
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyAnyRequest {
+    pub message: String,
+    pub signature: String,
+    /// Candidate signers, tried in order.
+    pub pubkeys: Vec<String>,
+    /// Encoding of `signature`; base64 by default.
+    #[serde(default)]
+    pub encoding: Encoding,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VerifyAnyData {
+    /// Index into `pubkeys` of the first key the signature verifies against,
+    /// or `null` if none does.
+    pub index: Option<usize>,
+    pub pubkey: Option<String>,
+    pub message: String,
+}
+
+/// Finds which of several candidate keys signed `message`, e.g. for multisig
+/// members or a rotated key. Uses the same strict check as `/message/verify`.
+pub async fn verify_any(
+    State(_): State<AppState>,
+    Json(payload): Json<VerifyAnyRequest>,
+) -> HandlerResult<VerifyAnyData> {
+    ensure_batch_size(payload.pubkeys.len())?;
+    let public_keys = payload
+        .pubkeys
+        .iter()
+        .enumerate()
+        .map(|(i, pubkey)| {
+            decode_public_key(pubkey)
+                .map_err(|e| AppError::new(e.code, format!("pubkeys[{}]: {}", i, e.message)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let signature = decode_signature(&payload.signature, payload.encoding)?;
+
+    let message_bytes = payload.message.as_bytes();
+    let index = public_keys
+        .iter()
+        .position(|public_key| public_key.verify_strict(message_bytes, &signature).is_ok());
+
+    Ok(ApiResponse::from(VerifyAnyData {
+        index,
+        pubkey: index.map(|i| payload.pubkeys[i].trim().to_string()),
+        message: payload.message,
+    }))
+}
+
+/// ------------------ /message/verify-batch ------------------
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyBatchRequest {
+    pub message: String,
+    pub pubkey: String,
+    pub signatures: Vec<String>,
+    /// Encoding of each signature; base64 by default.
+    #[serde(default)]
+    pub encoding: Encoding,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VerifyBatchData {
+    /// One entry per signature, in request order.
+    pub results: Vec<SignatureCheck>,
+    pub message: String,
+    pub pubkey: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SignatureCheck {
+    pub valid: bool,
+    /// Why the signature was rejected; omitted when `valid` is true.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Checks several signatures over one message by one key, e.g. to audit the
+/// signatures a key produced. Uses the same strict check as `/message/verify`.
+/// A signature that fails to decode is reported invalid rather than failing
+/// the batch; a bad `pubkey` is still a 400.
+pub async fn verify_batch(
+    State(_): State<AppState>,
+    Json(payload): Json<VerifyBatchRequest>,
+) -> HandlerResult<VerifyBatchData> {
+    ensure_batch_size(payload.signatures.len())?;
+    let public_key = decode_public_key(&payload.pubkey)?;
+
+    let message_bytes = payload.message.as_bytes();
+    let results = payload
+        .signatures
+        .iter()
+        .map(|signature| match decode_signature(signature, payload.encoding) {
+            Ok(signature) if public_key.verify_strict(message_bytes, &signature).is_ok() => {
+                SignatureCheck { valid: true, reason: None }
+            }
+            Ok(_) => SignatureCheck {
+                valid: false,
+                reason: Some("signature does not match".to_string()),
+            },
+            Err(e) => SignatureCheck {
+                valid: false,
+                reason: Some(e.message),
+            },
+        })
+        .collect();
+
+    Ok(ApiResponse::from(VerifyBatchData {
+        results,
+        message: payload.message,
+        pubkey: payload.pubkey,
+    }))
+}
+
+/// ------------------ /message/hash ------------------
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HashMessageRequest {
+    pub message: String,
+    /// `sha256` or `keccak256`.
+    pub algorithm: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HashMessageData {
+    pub algorithm: &'static str,
+    pub hex: String,
+    pub base58: String,
+    pub message: String,
+}
+
+/// Digests the UTF-8 bytes of `message`, so a client can sign the hash the
+/// same way an on-chain program computes it.
+pub async fn hash_message(
+    State(_): State<AppState>,
+    Json(payload): Json<HashMessageRequest>,
+) -> HandlerResult<HashMessageData> {
+    ensure_message_size(payload.message.len())?;
+
+    let message_bytes = payload.message.as_bytes();
+    let (algorithm, digest) = match payload.algorithm.trim().to_ascii_lowercase().as_str() {
+        "sha256" => ("sha256", Sha256::digest(message_bytes).to_vec()),
+        "keccak256" => ("keccak256", Keccak256::digest(message_bytes).to_vec()),
+        other => {
+            return Err(AppError::new(
+                ErrorCode::UnsupportedAlgorithm,
+                format!("Unsupported hash algorithm {:?}; expected sha256 or keccak256", other),
+            ))
         }
     };
 
-    let message_bytes = payload.message.as_bytes();
-    let is_valid = public_key.verify_strict(message_bytes, &signature).is_ok();
-
-    Json(ApiResponse {
-        success: true,
-        data: VerifyMessageData {
-            valid: is_valid,
-            message: payload.message,
-            pubkey: payload.pubkey,
-        },
-    })
+    Ok(ApiResponse::from(HashMessageData {
+        algorithm,
+        hex: hex::encode(&digest),
+        base58: bs58::encode(&digest).into_string(),
+        message: payload.message,
+    }))
+}
+
+/// ------------------ /message/sign/solana ------------------
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignSolanaMessageRequest {
+    /// Base64 of a bincode-serialized legacy `Message`.
+    pub message: String,
+    /// Base58 secret key; give either this or `keyAlias`.
+    #[serde(default)]
+    pub secret: Option<String>,
+    /// Name of a server-held key (`SERVER_KEYS`) to sign with instead.
+    #[serde(default)]
+    pub key_alias: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignSolanaMessageData {
+    pub signature: String,
+    pub pubkey: String,
+    pub signer_index: usize,
+}
+
+/// Signs a serialized Solana `Message` the way a transaction signer would,
+/// reporting which required-signer slot the signature belongs in.
+pub async fn sign_solana_message(
+    State(_): State<AppState>,
+    Json(payload): Json<SignSolanaMessageRequest>,
+) -> HandlerResult<SignSolanaMessageData> {
+    let message_bytes = STANDARD
+        .decode(&payload.message)
+        .map_err(|_| AppError::new(ErrorCode::InvalidMessage, "Invalid base64 message"))?;
+    ensure_message_size(message_bytes.len())?;
+    let message: Message = bincode::deserialize(&message_bytes)
+        .map_err(|_| AppError::new(ErrorCode::InvalidMessage, "Failed to deserialize Solana message"))?;
+
+    let secret_bytes = signing_secret(payload.secret.as_deref(), payload.key_alias.as_deref())?;
+    let keypair = SolanaKeypair::from_bytes(&secret_bytes).map_err(|_| {
+        AppError::new(ErrorCode::InvalidSecret, "Failed to parse secret key into Keypair")
+    })?;
+    let pubkey = keypair.pubkey();
+
+    let num_signers = message.header.num_required_signatures as usize;
+    let signer_index = message
+        .account_keys
+        .iter()
+        .take(num_signers)
+        .position(|key| *key == pubkey)
+        .ok_or_else(|| {
+            AppError::new(
+                ErrorCode::SignerNotInMessage,
+                format!("{} is not a required signer of this message", pubkey),
+            )
+        })?;
+
+    let signature = keypair.sign_message(&message.serialize());
+
+    Ok(ApiResponse::from(SignSolanaMessageData {
+        signature: STANDARD.encode(signature.as_ref()),
+        pubkey: pubkey.to_string(),
+        signer_index,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    async fn sign(body: serde_json::Value) -> SignMessageResponse {
+        let request: SignMessageRequest = serde_json::from_value(body).unwrap();
+        sign_message(State(AppState::for_tests()), Json(request))
+            .await
+            .unwrap()
+            .into_data()
+    }
+
+    #[tokio::test]
+    async fn sign_message_public_key_encodings_match() {
+        let keypair = SolanaKeypair::new();
+        let data = sign(json!({
+            "message": "hello",
+            "secret": keypair.to_base58_string(),
+        }))
+        .await;
+
+        let base58 = bs58::decode(&data.public_key).into_vec().unwrap();
+        let base64 = STANDARD.decode(&data.public_key_base64).unwrap();
+        assert_eq!(base58.len(), 32);
+        assert_eq!(base58, base64);
+        assert_eq!(base58, keypair.pubkey().to_bytes());
+    }
+
+    #[tokio::test]
+    async fn sign_message_signs_raw_base64_bytes() {
+        // Not valid UTF-8: 0xff never appears in it, and 0xc3 needs a
+        // continuation byte.
+        let bytes = [0xff, 0xfe, 0x00, 0xc3, 0x28];
+        let keypair = SolanaKeypair::new();
+        let data = sign(json!({
+            "message": STANDARD.encode(bytes),
+            "messageEncoding": "base64",
+            "secret": keypair.to_base58_string(),
+        }))
+        .await;
+
+        let signature = STANDARD.decode(&data.signature).unwrap();
+        let signature = Signature::from_bytes(&signature).unwrap();
+        let public_key = PublicKey::from_bytes(&keypair.pubkey().to_bytes()).unwrap();
+        assert!(public_key.verify(&bytes, &signature).is_ok());
+
+        let request: VerifyMessageRequest = serde_json::from_value(json!({
+            "message": data.message,
+            "messageEncoding": "base64",
+            "signature": data.signature,
+            "pubkey": data.public_key,
+        }))
+        .unwrap();
+        let verified = verify_message(State(AppState::for_tests()), Json(request))
+            .await
+            .unwrap()
+            .into_data();
+        assert!(verified.valid, "{:?}", verified.reason);
+    }
 }
@@ -0,0 +1,205 @@
+use std::future::ready;
+
+use axum::{
+    extract::State,
+    response::{IntoResponse, Response},
+    Json,
+};
+use futures_util::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_program::{program_option::COption, pubkey::Pubkey};
+use solana_sdk::account::Account;
+use spl_token_2022::{extension::StateWithExtensions, state::Mint};
+
+use crate::error::AppError;
+use crate::ndjson::ndjson_response;
+use crate::proto::ResponseFormat;
+use crate::response::ApiResponse;
+use crate::rpc::{rpc_error, within};
+use crate::state::AppState;
+use crate::types::{ensure_batch_size, parse_pubkey};
+
+/// `getMultipleAccounts` accepts at most 100 keys per call.
+const MAX_ACCOUNTS_PER_RPC_CALL: usize = 100;
+
+/// A quoted base58 pubkey (at most 44 characters) plus separator, with slack
+/// for whitespace, for the route's body limit.
+pub const MAX_MINT_ENTRY_BYTES: usize = 64;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MintInfoBatchRequest {
+    pub mints: Vec<String>,
+    /// Deadline for each RPC call, in milliseconds; clamped to
+    /// `MAX_RPC_TIMEOUT_MS`.
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MintInfoEntry {
+    pub mint: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decimals: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supply: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mint_authority: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub freeze_authority: Option<String>,
+    /// Set instead of the fields above when this entry couldn't be resolved.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Resolves decimals and supply for many mints in one RPC round trip per 100 mints.
+/// Bad entries carry an inline `error`; only RPC failure fails the request.
+///
+/// With `Accept: application/x-ndjson` each entry is streamed as its own line
+/// as soon as its chunk resolves.
+pub async fn mint_info_batch(
+    State(state): State<AppState>,
+    format: ResponseFormat,
+    Json(payload): Json<MintInfoBatchRequest>,
+) -> Result<Response, AppError> {
+    ensure_batch_size(payload.mints.len())?;
+    let rpc = state.rpc()?;
+    let timeout_ms = payload.timeout_ms;
+
+    let chunks: Vec<Vec<String>> = payload
+        .mints
+        .chunks(MAX_ACCOUNTS_PER_RPC_CALL)
+        .map(<[String]>::to_vec)
+        .collect();
+
+    if format == ResponseFormat::Ndjson {
+        let entries = stream::iter(chunks)
+            .then(move |chunk| {
+                let rpc = rpc.clone();
+                async move { resolve_chunk(&rpc, chunk, timeout_ms).await }
+            })
+            .flat_map(|resolved| match resolved {
+                Ok(entries) => stream::iter(entries.into_iter().map(Ok)).left_stream(),
+                Err(e) => stream::once(ready(Err(e))).right_stream(),
+            });
+        return Ok(ndjson_response(entries));
+    }
+
+    let mut entries = Vec::with_capacity(payload.mints.len());
+    for chunk in chunks {
+        entries.extend(resolve_chunk(&rpc, chunk, timeout_ms).await?);
+    }
+
+    Ok(ApiResponse::from(entries).into_response())
+}
+
+/// Resolves up to `MAX_ACCOUNTS_PER_RPC_CALL` mints with a single RPC call.
+async fn resolve_chunk(
+    rpc: &RpcClient,
+    mints: Vec<String>,
+    timeout_ms: Option<u64>,
+) -> Result<Vec<MintInfoEntry>, AppError> {
+    let parsed: Vec<Result<Pubkey, AppError>> = mints
+        .iter()
+        .map(|mint| parse_pubkey(mint, "mint"))
+        .collect();
+    let valid: Vec<Pubkey> = parsed.iter().filter_map(|p| p.as_ref().ok().copied()).collect();
+
+    let fetched = if valid.is_empty() {
+        Vec::new()
+    } else {
+        within(timeout_ms, rpc.get_multiple_accounts(&valid))
+            .await?
+            .map_err(rpc_error)?
+    };
+    let mut accounts = fetched.into_iter();
+
+    Ok(mints
+        .into_iter()
+        .zip(parsed)
+        .map(|(mint, pubkey)| match pubkey {
+            Ok(_) => mint_entry(mint, accounts.next().flatten()),
+            Err(e) => MintInfoEntry {
+                mint,
+                error: Some(e.message),
+                ..Default::default()
+            },
+        })
+        .collect())
+}
+
+fn mint_entry(mint: String, account: Option<Account>) -> MintInfoEntry {
+    let not_a_mint = "Account is not a token mint";
+    let state = match account {
+        None => Err("Account not found"),
+        Some(account)
+            if account.owner != spl_token::id() && account.owner != spl_token_2022::id() =>
+        {
+            Err(not_a_mint)
+        }
+        Some(account) => StateWithExtensions::<Mint>::unpack(&account.data)
+            .map(|state| state.base)
+            .map_err(|_| not_a_mint),
+    };
+
+    match state {
+        Ok(state) => MintInfoEntry {
+            mint,
+            decimals: Some(state.decimals),
+            supply: Some(state.supply),
+            mint_authority: coption_to_string(state.mint_authority),
+            freeze_authority: coption_to_string(state.freeze_authority),
+            error: None,
+        },
+        Err(e) => MintInfoEntry {
+            mint,
+            error: Some(e.to_string()),
+            ..Default::default()
+        },
+    }
+}
+
+fn coption_to_string(key: COption<Pubkey>) -> Option<String> {
+    Option::from(key).map(|k: Pubkey| k.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_program::program_pack::Pack;
+
+    use super::*;
+
+    fn mint_account(owner: Pubkey) -> Account {
+        let mut data = vec![0; Mint::LEN];
+        let mint = Mint {
+            decimals: 9,
+            supply: 500,
+            is_initialized: true,
+            ..Default::default()
+        };
+        Mint::pack(mint, &mut data).unwrap();
+        Account {
+            owner,
+            data,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn mint_entry_reads_both_token_programs() {
+        for owner in [spl_token::id(), spl_token_2022::id()] {
+            let entry = mint_entry("mint".to_string(), Some(mint_account(owner)));
+            assert_eq!(entry.error, None, "{}", owner);
+            assert_eq!(entry.decimals, Some(9));
+            assert_eq!(entry.supply, Some(500));
+        }
+    }
+
+    #[test]
+    fn mint_entry_rejects_other_owners() {
+        let entry = mint_entry("mint".to_string(), Some(mint_account(Pubkey::new_unique())));
+        assert_eq!(entry.error.as_deref(), Some("Account is not a token mint"));
+    }
+}
@@ -1,4 +1,20 @@
+pub mod account;
+pub mod admin;
+pub mod amount;
+pub mod balance;
+pub mod blockhash;
+pub mod health;
+pub mod instructions;
 pub mod keypair;
 pub mod token;
 pub mod message;
+pub mod mint_info;
+pub mod not_found;
+pub mod precompile;
+pub mod pubkey;
+pub mod rent;
+pub mod stats;
+pub mod sysvar;
+pub mod transaction;
 pub mod transfer;
+pub mod version;
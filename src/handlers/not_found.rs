@@ -0,0 +1,27 @@
+use axum::{
+    http::{StatusCode, Uri},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+
+use crate::error::ErrorCode;
+
+#[derive(Debug, Serialize)]
+pub struct NotFoundBody {
+    pub success: bool,
+    pub error: &'static str,
+    pub code: ErrorCode,
+    pub path: String,
+}
+
+/// Router fallback so unknown paths get the standard JSON error shape.
+pub async fn not_found(uri: Uri) -> Response {
+    let body = NotFoundBody {
+        success: false,
+        error: "route not found",
+        code: ErrorCode::RouteNotFound,
+        path: uri.path().to_string(),
+    };
+    (StatusCode::NOT_FOUND, Json(body)).into_response()
+}
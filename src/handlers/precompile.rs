@@ -0,0 +1,200 @@
+use axum::{extract::State, Json};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::Deserialize;
+use solana_sdk::{
+    ed25519_instruction::{
+        DATA_START, PUBKEY_SERIALIZED_SIZE, SIGNATURE_OFFSETS_START, SIGNATURE_SERIALIZED_SIZE,
+    },
+    ed25519_program,
+    instruction::Instruction,
+    secp256k1_instruction::{
+        SecpSignatureOffsets, DATA_START as SECP_DATA_START, HASHED_PUBKEY_SERIALIZED_SIZE,
+        SIGNATURE_SERIALIZED_SIZE as SECP_SIGNATURE_SERIALIZED_SIZE,
+    },
+    secp256k1_program,
+};
+use tracing::info_span;
+
+use crate::error::{AppError, ErrorCode};
+use crate::response::{ApiResponse, HandlerResult};
+use crate::state::AppState;
+use crate::types::{
+    instruction_to_response, parse_pubkey, Encoding, InstructionType, TokenInstructionResponse,
+};
+
+/// Offsets set to this index point at the verify instruction's own data.
+const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+/// ------------------ /instructions/ed25519 ------------------
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Ed25519InstructionRequest {
+    pub pubkey: String,
+    pub message: String,
+    /// Base64, matching `/message/sign` output.
+    pub signature: String,
+}
+
+/// Builds an ed25519 native-program instruction so a program can check the
+/// signature on-chain (via the instructions sysvar). Unlike `/message/verify`
+/// nothing is verified here; the runtime does that when the transaction runs.
+pub async fn ed25519_instruction(
+    State(_): State<AppState>,
+    Json(payload): Json<Ed25519InstructionRequest>,
+) -> HandlerResult<TokenInstructionResponse> {
+    let pubkey = parse_pubkey(&payload.pubkey, "pubkey")?;
+    let _span = info_span!(
+        "ed25519_instruction",
+        program_id = %ed25519_program::id(),
+        instruction_type = InstructionType::Ed25519Verify.as_str(),
+        %pubkey,
+    )
+    .entered();
+    let signature = STANDARD
+        .decode(&payload.signature)
+        .ok()
+        .filter(|bytes| bytes.len() == SIGNATURE_SERIALIZED_SIZE)
+        .ok_or_else(|| {
+            AppError::new(ErrorCode::InvalidSignature, "Signature must be 64 bytes, base64-encoded")
+        })?;
+    let message = payload.message.as_bytes();
+
+    let public_key_offset = DATA_START;
+    let signature_offset = public_key_offset + PUBKEY_SERIALIZED_SIZE;
+    let message_data_offset = signature_offset + SIGNATURE_SERIALIZED_SIZE;
+    let message_data_size = u16::try_from(message.len())
+        .ok()
+        .filter(|_| message_data_offset + message.len() <= u16::MAX as usize)
+        .ok_or_else(|| {
+            AppError::new(ErrorCode::MessageTooLarge, "Message is too large for an ed25519 instruction")
+        })?;
+
+    let mut data = Vec::with_capacity(message_data_offset + message.len());
+    // Signature count followed by a padding byte that keeps the offsets aligned.
+    data.extend_from_slice(&[1, 0]);
+    debug_assert_eq!(data.len(), SIGNATURE_OFFSETS_START);
+    for field in [
+        signature_offset as u16,
+        CURRENT_INSTRUCTION,
+        public_key_offset as u16,
+        CURRENT_INSTRUCTION,
+        message_data_offset as u16,
+        message_data_size,
+        CURRENT_INSTRUCTION,
+    ] {
+        data.extend_from_slice(&field.to_le_bytes());
+    }
+    data.extend_from_slice(pubkey.as_ref());
+    data.extend_from_slice(&signature);
+    data.extend_from_slice(message);
+
+    let ix = Instruction {
+        program_id: ed25519_program::id(),
+        accounts: vec![],
+        data,
+    };
+
+    Ok(ApiResponse::from(instruction_to_response(
+        &ix,
+        InstructionType::Ed25519Verify,
+        Encoding::Base64,
+    )))
+}
+
+/// ------------------ /instructions/secp256k1 ------------------
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Secp256k1InstructionRequest {
+    /// 20-byte Ethereum address, hex with or without `0x`.
+    pub eth_address: String,
+    pub message: String,
+    /// 65-byte `r || s || v` signature in hex; `v` may be 0/1 or 27/28.
+    pub signature: String,
+    /// Position this instruction will occupy in the transaction. The offsets
+    /// reference it, so it must match or verification fails.
+    #[serde(default)]
+    pub instruction_index: u8,
+}
+
+/// Builds a secp256k1 native-program instruction for verifying an
+/// Ethereum-style signature on-chain. The program keccak256-hashes `message`
+/// itself, so pass the original message rather than its hash.
+pub async fn secp256k1_instruction(
+    State(_): State<AppState>,
+    Json(payload): Json<Secp256k1InstructionRequest>,
+) -> HandlerResult<TokenInstructionResponse> {
+    let _span = info_span!(
+        "secp256k1_instruction",
+        program_id = %secp256k1_program::id(),
+        instruction_type = InstructionType::Secp256k1Verify.as_str(),
+    )
+    .entered();
+
+    let eth_address = Encoding::Hex.decode(&payload.eth_address).ok()
+        .filter(|bytes| bytes.len() == HASHED_PUBKEY_SERIALIZED_SIZE)
+        .ok_or_else(|| {
+            AppError::new(ErrorCode::InvalidPubkey, "ethAddress must be 20 bytes of hex")
+        })?;
+    let mut signature = Encoding::Hex.decode(&payload.signature).ok()
+        .filter(|bytes| bytes.len() == SECP_SIGNATURE_SERIALIZED_SIZE + 1)
+        .ok_or_else(|| {
+            AppError::new(ErrorCode::InvalidSignature, "Signature must be 65 bytes of hex (r || s || v)")
+        })?;
+    let recovery_id = match signature[SECP_SIGNATURE_SERIALIZED_SIZE] {
+        v @ (0 | 1) => v,
+        v @ (27 | 28) => v - 27,
+        _ => {
+            return Err(AppError::new(
+                ErrorCode::InvalidSignature,
+                "Signature recovery id must be 0, 1, 27 or 28",
+            ))
+        }
+    };
+    signature[SECP_SIGNATURE_SERIALIZED_SIZE] = recovery_id;
+    let message = payload.message.as_bytes();
+
+    let eth_address_offset = SECP_DATA_START;
+    let signature_offset = eth_address_offset + HASHED_PUBKEY_SERIALIZED_SIZE;
+    let message_data_offset = signature_offset + signature.len();
+    let message_data_size = u16::try_from(message.len())
+        .ok()
+        .filter(|_| message_data_offset + message.len() <= u16::MAX as usize)
+        .ok_or_else(|| {
+            AppError::new(ErrorCode::MessageTooLarge, "Message is too large for a secp256k1 instruction")
+        })?;
+
+    let offsets = SecpSignatureOffsets {
+        signature_offset: signature_offset as u16,
+        signature_instruction_index: payload.instruction_index,
+        eth_address_offset: eth_address_offset as u16,
+        eth_address_instruction_index: payload.instruction_index,
+        message_data_offset: message_data_offset as u16,
+        message_data_size,
+        message_instruction_index: payload.instruction_index,
+    };
+    let offsets = bincode::serialize(&offsets)
+        .map_err(|e| AppError::new(ErrorCode::InstructionBuildFailed, e.to_string()))?;
+
+    let mut data = Vec::with_capacity(message_data_offset + message.len());
+    data.push(1);
+    data.extend_from_slice(&offsets);
+    debug_assert_eq!(data.len(), eth_address_offset);
+    data.extend_from_slice(&eth_address);
+    data.extend_from_slice(&signature);
+    data.extend_from_slice(message);
+
+    let ix = Instruction {
+        program_id: secp256k1_program::id(),
+        accounts: vec![],
+        data,
+    };
+
+    Ok(ApiResponse::from(instruction_to_response(
+        &ix,
+        InstructionType::Secp256k1Verify,
+        Encoding::Base64,
+    )))
+}
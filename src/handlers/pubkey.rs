@@ -0,0 +1,33 @@
+use axum::{extract::State, Json};
+use serde::{Deserialize, Serialize};
+
+use crate::response::ApiResponse;
+use crate::state::AppState;
+use crate::types::parse_pubkey;
+
+#[derive(Debug, Deserialize)]
+pub struct ValidatePubkeyRequest {
+    pub pubkey: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatePubkeyData {
+    pub valid: bool,
+    /// Whether the key is an ed25519 point, i.e. could have a private key.
+    /// PDAs are off the curve. False when `valid` is false.
+    pub on_curve: bool,
+}
+
+/// Checks an address input. A malformed key is `valid: false`, not an error.
+pub async fn validate_pubkey(
+    State(_): State<AppState>,
+    Json(payload): Json<ValidatePubkeyRequest>,
+) -> ApiResponse<ValidatePubkeyData> {
+    let pubkey = parse_pubkey(&payload.pubkey, "pubkey").ok();
+
+    ApiResponse::from(ValidatePubkeyData {
+        valid: pubkey.is_some(),
+        on_curve: pubkey.is_some_and(|pubkey| pubkey.is_on_curve()),
+    })
+}
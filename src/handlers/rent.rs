@@ -0,0 +1,63 @@
+use axum::{extract::State, Json};
+use serde::{Deserialize, Serialize};
+use solana_program::rent::Rent;
+use spl_token_2022::extension::{AccountType, ExtensionType};
+
+use crate::error::{AppError, ErrorCode};
+use crate::handlers::token::mint_space;
+use crate::response::{ApiResponse, HandlerResult};
+use crate::state::AppState;
+use crate::types::{resolve_token_program, TokenProgram};
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MintRentRequest {
+    /// `token` or `token-2022`; defaults to the server's `DEFAULT_TOKEN_PROGRAM`.
+    #[serde(default)]
+    pub token_program: Option<TokenProgram>,
+    /// Token-2022 mint extensions, e.g. `mintCloseAuthority` or
+    /// `transferHook`.
+    #[serde(default)]
+    pub extensions: Vec<ExtensionType>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MintRentData {
+    /// Mint account size in bytes, including any extensions.
+    pub space: usize,
+    /// Rent-exempt minimum balance for `space`.
+    pub lamports: u64,
+}
+
+/// Rent-exempt minimum for a new mint under the default (mainnet) rent
+/// parameters, for funding `/token/create` without RPC access.
+pub async fn mint_rent(
+    State(_): State<AppState>,
+    Json(payload): Json<MintRentRequest>,
+) -> HandlerResult<MintRentData> {
+    if !payload.extensions.is_empty()
+        && resolve_token_program(payload.token_program) != spl_token_2022::id()
+    {
+        return Err(AppError::new(
+            ErrorCode::UnsupportedExtension,
+            "extensions need tokenProgram token-2022",
+        ));
+    }
+    if let Some(extension) = payload
+        .extensions
+        .iter()
+        .find(|extension| extension.get_account_type() != AccountType::Mint)
+    {
+        return Err(AppError::new(
+            ErrorCode::UnsupportedExtension,
+            format!("{:?} is not a mint extension", extension),
+        ));
+    }
+
+    let space = mint_space(&payload.extensions)?;
+    Ok(ApiResponse::from(MintRentData {
+        space,
+        lamports: Rent::default().minimum_balance(space),
+    }))
+}
@@ -0,0 +1,23 @@
+use axum::extract::{Query, State};
+use serde::Deserialize;
+
+use crate::response::ApiResponse;
+use crate::state::AppState;
+use crate::stats::StatsSnapshot;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct StatsQuery {
+    /// Zero the counters after reading them, for scrapers that want
+    /// per-interval deltas.
+    #[serde(default)]
+    pub reset: bool,
+}
+
+/// Request, per-endpoint and RPC call counters as JSON: a dependency-free
+/// alternative to a Prometheus exporter.
+pub async fn stats(
+    State(state): State<AppState>,
+    Query(query): Query<StatsQuery>,
+) -> ApiResponse<StatsSnapshot> {
+    ApiResponse::from(state.stats.snapshot(query.reset))
+}
@@ -0,0 +1,59 @@
+use axum::extract::State;
+use serde::Serialize;
+use solana_program::{clock::Clock, sysvar};
+
+use crate::error::{AppError, ErrorCode};
+use crate::response::{ApiResponse, HandlerResult};
+use crate::rpc::{fetch_account, rpc_error};
+use crate::state::AppState;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClockData {
+    pub slot: u64,
+    pub epoch: u64,
+    pub unix_timestamp: i64,
+}
+
+/// The cluster clock as programs see it, read from the clock sysvar account.
+pub async fn clock(State(state): State<AppState>) -> HandlerResult<ClockData> {
+    let rpc = state.rpc()?;
+    let account = fetch_account(&rpc, &sysvar::clock::id(), rpc.commitment())
+        .await
+        .map_err(rpc_error)?
+        .ok_or_else(|| AppError::new(ErrorCode::RpcError, "RPC returned no clock sysvar"))?;
+    let clock: Clock = bincode::deserialize(&account.data).map_err(|_| {
+        AppError::new(ErrorCode::RpcError, "RPC returned malformed clock sysvar data")
+    })?;
+
+    Ok(ApiResponse::from(ClockData {
+        slot: clock.slot,
+        epoch: clock.epoch,
+        unix_timestamp: clock.unix_timestamp,
+    }))
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EpochInfoData {
+    pub epoch: u64,
+    /// Slots elapsed in the current epoch.
+    pub slot_index: u64,
+    pub slots_in_epoch: u64,
+    pub absolute_slot: u64,
+    pub block_height: u64,
+}
+
+/// Progress through the current epoch, from `getEpochInfo`.
+pub async fn epoch_info(State(state): State<AppState>) -> HandlerResult<EpochInfoData> {
+    let rpc = state.rpc()?;
+    let info = rpc.get_epoch_info().await.map_err(rpc_error)?;
+
+    Ok(ApiResponse::from(EpochInfoData {
+        epoch: info.epoch,
+        slot_index: info.slot_index,
+        slots_in_epoch: info.slots_in_epoch,
+        absolute_slot: info.absolute_slot,
+        block_height: info.block_height,
+    }))
+}
@@ -1,77 +1,298 @@
-use std::str::FromStr;
-
-use axum::Json;
-use base64;
+use axum::{
+    extract::{Query, State},
+    Json,
+};
 use serde::{Deserialize, Serialize};
-use solana_program::pubkey::Pubkey;
-use spl_token::instruction::{initialize_mint, mint_to};
+use solana_program::{instruction::Instruction, pubkey::Pubkey, rent::Rent, system_instruction};
+use solana_sdk::signature::{Keypair, Signer};
+use spl_associated_token_account::{
+    get_associated_token_address_with_program_id, instruction::create_associated_token_account,
+};
+use spl_token_2022::{
+    extension::{
+        transfer_fee::{self, MAX_FEE_BASIS_POINTS},
+        transfer_hook, AccountType, ExtensionType,
+    },
+    instruction::{
+        approve, approve_checked, burn_checked, close_account, freeze_account, initialize_account,
+        initialize_account3, initialize_mint, initialize_mint_close_authority,
+        initialize_multisig, mint_to, MAX_SIGNERS, MIN_SIGNERS,
+    },
+    state::{Account, Mint},
+};
+use tracing::info_span;
+
+use crate::config;
+use crate::error::{AppError, ErrorCode};
+use crate::handlers::keypair::KeypairData;
+use crate::proto::{Negotiated, ResponseFormat, ToProto};
+use crate::response::{ApiResponse, HandlerResult};
+use crate::handlers::balance::fetch_mint_state;
+use crate::rpc::{rpc_error, within};
+use crate::state::AppState;
+use crate::types::{
+    checked_add_amount, ensure_batch_size, instruction_to_response, parse_mint, parse_pubkey, resolve_token_program,
+    with_compiled, BuildOutcome, CompiledQuery, DryRunQuery, Encoding, InstructionSequence,
+    InstructionType, TokenInstructionResponse, TokenProgram, WithCompiled,
+};
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateTokenRequest {
     pub mint_authority: String,
-    pub mint: String,
+    /// Omit (or set `generateMint`) to have the server generate the mint.
+    #[serde(default)]
+    pub mint: Option<String>,
     pub decimals: u8,
+    /// Generate a fresh mint keypair and prepend its `create_account`.
+    ///
+    /// The server then creates the mint's secret key and sends it back in
+    /// the response: it passes through this process and over the wire, so
+    /// only use this where that is acceptable. The key is not stored.
+    #[serde(default)]
+    pub generate_mint: bool,
+    /// Permits the all-zeros pubkey for `mint`/`mintAuthority`. Almost always a
+    /// client bug, so it is rejected unless explicitly allowed.
+    #[serde(default)]
+    pub allow_default_pubkey: bool,
+    /// Prepend the `create_account` instruction that allocates and funds the
+    /// mint account, returning an ordered instruction list.
+    #[serde(default)]
+    pub create_account: bool,
+    /// Funds the new mint account. Defaults to `mintAuthority`.
+    #[serde(default)]
+    pub payer: Option<String>,
+    /// `token` or `token-2022`; defaults to the server's `DEFAULT_TOKEN_PROGRAM`.
+    #[serde(default)]
+    pub token_program: Option<TokenProgram>,
+    /// Token-2022 only: initializes the mint-close-authority extension so this
+    /// key can later close the mint. Like any extension, it needs
+    /// `createAccount` or `generateMint`.
+    #[serde(default)]
+    pub close_authority: Option<String>,
+    /// Token-2022 only: initializes the transfer-hook extension pointing at
+    /// this program, with `mintAuthority` as the hook authority.
+    #[serde(default)]
+    pub transfer_hook_program_id: Option<String>,
 }
 
+/// A single `initialize_mint`, or the full sequence when `createAccount` is set
+/// or extensions are requested.
 #[derive(Debug, Serialize)]
-pub struct AccountMeta {
-    pub pubkey: String,
-    pub is_signer: bool,
-    pub is_writable: bool,
+#[serde(untagged)]
+pub enum CreateTokenResponse {
+    Instruction(TokenInstructionResponse),
+    Sequence(InstructionSequence),
 }
 
-#[derive(Debug, Serialize)]
-pub struct TokenInstructionResponse {
-    pub program_id: String,
-    pub accounts: Vec<AccountMeta>,
-    pub instruction_data: String,
+impl ToProto for CreateTokenResponse {
+    fn encode_proto(&self) -> Option<Vec<u8>> {
+        match self {
+            CreateTokenResponse::Instruction(ix) => ix.encode_proto(),
+            CreateTokenResponse::Sequence(sequence) => sequence.encode_proto(),
+        }
+    }
 }
 
-#[derive(Debug, Serialize)]
-pub struct ApiResponse<T> {
-    pub success: bool,
-    pub data: T,
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateTokenData {
+    #[serde(flatten)]
+    pub instructions: CreateTokenResponse,
+    /// The server-generated mint in `generateMint` mode, secret included; it
+    /// must sign the `create_account`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub generated_mint: Option<KeypairData>,
 }
 
-pub async fn create_token(Json(payload): Json<CreateTokenRequest>) -> Json<ApiResponse<TokenInstructionResponse>> {
-    let mint = match Pubkey::from_str(&payload.mint) {
-        Ok(m) => m,
-        Err(_) => return Json(ApiResponse { success: false, data: TokenInstructionResponse { program_id: "".to_string(), accounts: vec![], instruction_data: "Invalid mint pubkey".to_string() } }),
-    };
+impl ToProto for CreateTokenData {
+    /// The protobuf schema has no room for the generated secret, so that mode
+    /// always answers in JSON.
+    fn encode_proto(&self) -> Option<Vec<u8>> {
+        match self.generated_mint {
+            Some(_) => None,
+            None => self.instructions.encode_proto(),
+        }
+    }
+}
 
-    let mint_authority = match Pubkey::from_str(&payload.mint_authority) {
-        Ok(m) => m,
-        Err(_) => return Json(ApiResponse { success: false, data: TokenInstructionResponse { program_id: "".to_string(), accounts: vec![], instruction_data: "Invalid mintAuthority pubkey".to_string() } }),
+pub async fn create_token(
+    State(_): State<AppState>,
+    format: ResponseFormat,
+    Query(query): Query<DryRunQuery>,
+    Query(compiled): Query<CompiledQuery>,
+    Json(payload): Json<CreateTokenRequest>,
+) -> Result<Negotiated<BuildOutcome<WithCompiled<CreateTokenData>>>, AppError> {
+    let (mint, generated) = match (payload.mint.as_deref(), payload.generate_mint) {
+        (Some(_), true) => {
+            return Err(AppError::new(
+                ErrorCode::ConflictingFields,
+                "Give either mint or generateMint, not both",
+            ))
+        }
+        (Some(mint), false) => (parse_mint(mint)?, None),
+        (None, _) if config::get().mint_whitelist.is_some() => {
+            return Err(AppError::new(
+                ErrorCode::MintNotAllowed,
+                "mint not allowed: a generated mint can't be in MINT_WHITELIST",
+            ))
+        }
+        (None, _) => {
+            let keypair = Keypair::new();
+            (keypair.pubkey(), Some(keypair))
+        }
+    };
+    let mint_authority = parse_pubkey(&payload.mint_authority, "mintAuthority")?;
+    let payer = match &payload.payer {
+        Some(payer) => parse_pubkey(payer, "payer")?,
+        None => mint_authority,
     };
 
-    let ix = match initialize_mint(
-        &spl_token::id(),
+    if !payload.allow_default_pubkey {
+        reject_default_pubkey(&mint, "mint")?;
+        reject_default_pubkey(&mint_authority, "mintAuthority")?;
+    }
+
+    let close_authority = payload
+        .close_authority
+        .as_deref()
+        .map(|key| parse_pubkey(key, "closeAuthority"))
+        .transpose()?;
+    let transfer_hook_program_id = payload
+        .transfer_hook_program_id
+        .as_deref()
+        .map(|key| parse_pubkey(key, "transferHookProgramId"))
+        .transpose()?;
+
+    let token_program = resolve_token_program(payload.token_program);
+    let extensions: Vec<ExtensionType> = [
+        close_authority.map(|_| ExtensionType::MintCloseAuthority),
+        transfer_hook_program_id.map(|_| ExtensionType::TransferHook),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    if !extensions.is_empty() && token_program != spl_token_2022::id() {
+        return Err(AppError::new(
+            ErrorCode::UnsupportedExtension,
+            "closeAuthority and transferHookProgramId need tokenProgram token-2022",
+        ));
+    }
+    // A caller-supplied mint account could be too small for the extensions,
+    // so it must be allocated here, sized for them.
+    if !extensions.is_empty() && !payload.create_account && generated.is_none() {
+        return Err(AppError::new(
+            ErrorCode::UnsupportedExtension,
+            "closeAuthority and transferHookProgramId need createAccount or generateMint, \
+             so the mint account is sized for the extensions",
+        ));
+    }
+
+    let _span = info_span!(
+        "create_token",
+        program_id = %token_program,
+        instruction_type = InstructionType::InitializeMint.as_str(),
+        %mint,
+        %mint_authority,
+    )
+    .entered();
+
+    if query.dry_run {
+        return Ok(format.negotiate(BuildOutcome::validated()));
+    }
+
+    // Extensions must be initialized before the mint itself.
+    let mut steps = Vec::new();
+    if payload.create_account || generated.is_some() {
+        steps.push((
+            InstructionType::SystemCreateAccount,
+            create_mint_account(&payer, &mint, &token_program, &extensions)?,
+        ));
+    }
+    if let Some(close_authority) = close_authority {
+        steps.push((
+            InstructionType::InitializeMintCloseAuthority,
+            initialize_mint_close_authority(&token_program, &mint, Some(&close_authority))
+                .map_err(|e| AppError::new(ErrorCode::InstructionBuildFailed, e.to_string()))?,
+        ));
+    }
+    if let Some(program_id) = transfer_hook_program_id {
+        steps.push((
+            InstructionType::InitializeTransferHook,
+            transfer_hook::instruction::initialize(
+                &token_program,
+                &mint,
+                Some(mint_authority),
+                Some(program_id),
+            )
+            .map_err(|e| AppError::new(ErrorCode::InstructionBuildFailed, e.to_string()))?,
+        ));
+    }
+    let initialize = initialize_mint(
+        &token_program,
         &mint,
         &mint_authority,
         None,
         payload.decimals,
-    ) {
-        Ok(ix) => ix,
-        Err(e) => return Json(ApiResponse { success: false, data: TokenInstructionResponse { program_id: "".to_string(), accounts: vec![], instruction_data: e.to_string() } }),
+    )
+    .map_err(|e| AppError::new(ErrorCode::InstructionBuildFailed, e.to_string()))?;
+    steps.push((InstructionType::InitializeMint, initialize));
+
+    let response = match steps.as_slice() {
+        [(instruction_type, initialize)] => CreateTokenResponse::Instruction(
+            instruction_to_response(initialize, *instruction_type, Encoding::Base64),
+        ),
+        sequence => CreateTokenResponse::Sequence(InstructionSequence::new(
+            sequence.iter().map(|(instruction_type, ix)| (*instruction_type, ix)),
+        )),
     };
+    let response = CreateTokenData {
+        instructions: response,
+        generated_mint: generated.map(|keypair| KeypairData {
+            pubkey: keypair.pubkey().to_string(),
+            secret: bs58::encode(keypair.to_bytes()).into_string(),
+        }),
+    };
+    let instructions: Vec<Instruction> = steps.into_iter().map(|(_, ix)| ix).collect();
+    let response = with_compiled(response, &compiled, &instructions, Some(&payer));
+
+    Ok(format.negotiate(BuildOutcome::Built(response)))
+}
 
-    let accounts: Vec<AccountMeta> = ix.accounts.iter().map(|meta| AccountMeta {
-        pubkey: meta.pubkey.to_string(),
-        is_signer: meta.is_signer,
-        is_writable: meta.is_writable,
-    }).collect();
+/// Allocates a rent-exempt, token-program-owned account sized for a mint plus
+/// any Token-2022 `extensions`. Rent uses the default (mainnet) parameters, so
+/// no RPC call is needed.
+pub fn create_mint_account(
+    payer: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    extensions: &[ExtensionType],
+) -> Result<Instruction, AppError> {
+    let space = mint_space(extensions)
+        .map_err(|e| AppError::new(ErrorCode::InstructionBuildFailed, e.message))?;
+    Ok(system_instruction::create_account(
+        payer,
+        mint,
+        Rent::default().minimum_balance(space),
+        space as u64,
+        token_program,
+    ))
+}
 
-    let encoded_data = base64::encode(ix.data);
+/// Size of a mint account with `extensions`: `Mint::LEN` when there are none.
+pub fn mint_space(extensions: &[ExtensionType]) -> Result<usize, AppError> {
+    ExtensionType::try_calculate_account_len::<Mint>(extensions)
+        .map_err(|e| AppError::new(ErrorCode::UnsupportedExtension, e.to_string()))
+}
 
-    Json(ApiResponse {
-        success: true,
-        data: TokenInstructionResponse {
-            program_id: ix.program_id.to_string(),
-            accounts,
-            instruction_data: encoded_data,
-        },
-    })
+fn reject_default_pubkey(pubkey: &Pubkey, field: &str) -> Result<(), AppError> {
+    if *pubkey == Pubkey::default() {
+        return Err(AppError::new(
+            ErrorCode::DefaultPubkey,
+            format!("{} must not be the default (all-zeros) pubkey", field),
+        ));
+    }
+    Ok(())
 }
 
 #[derive(Debug, Deserialize)]
@@ -81,47 +302,995 @@ pub struct MintTokenRequest {
     pub destination: String,
     pub authority: String,
     pub amount: u64,
+    /// `token` or `token-2022`; defaults to the server's `DEFAULT_TOKEN_PROGRAM`.
+    #[serde(default)]
+    pub token_program: Option<TokenProgram>,
+    /// Refuse to build the instruction if it would take the mint's supply,
+    /// read over RPC, past this. Best-effort: supply can still change before
+    /// the transaction lands. Needs `RPC_URL`.
+    #[serde(default)]
+    pub max_supply: Option<u64>,
+    /// Deadline for the supply lookup, in milliseconds; clamped to
+    /// `MAX_RPC_TIMEOUT_MS`.
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
 }
 
-pub async fn mint_token(Json(payload): Json<MintTokenRequest>) -> Json<ApiResponse<TokenInstructionResponse>> {
-    let mint = match Pubkey::from_str(&payload.mint) {
-        Ok(p) => p,
-        Err(_) => return Json(ApiResponse { success: false, data: TokenInstructionResponse { program_id: "".to_string(), accounts: vec![], instruction_data: "Invalid mint pubkey".to_string() } }),
-    };
+pub async fn mint_token(
+    State(state): State<AppState>,
+    format: ResponseFormat,
+    Query(query): Query<DryRunQuery>,
+    Query(compiled): Query<CompiledQuery>,
+    Json(payload): Json<MintTokenRequest>,
+) -> Result<Negotiated<BuildOutcome<WithCompiled<TokenInstructionResponse>>>, AppError> {
+    let mint = parse_mint(&payload.mint)?;
+    let destination = parse_pubkey(&payload.destination, "destination")?;
+    let authority = parse_pubkey(&payload.authority, "authority")?;
+    if let Some(max_supply) = payload.max_supply {
+        let rpc = state.rpc()?;
+        let (_, mint_state) = within(payload.timeout_ms, fetch_mint_state(&rpc, &mint)).await??;
+        let new_supply = checked_add_amount(mint_state.supply, payload.amount)?;
+        if new_supply > max_supply {
+            return Err(AppError::new(
+                ErrorCode::InvalidAmount,
+                format!(
+                    "Minting {} would raise supply from {} to {}, over maxSupply {}",
+                    payload.amount, mint_state.supply, new_supply, max_supply
+                ),
+            ));
+        }
+    }
 
-    let destination = match Pubkey::from_str(&payload.destination) {
-        Ok(p) => p,
-        Err(_) => return Json(ApiResponse { success: false, data: TokenInstructionResponse { program_id: "".to_string(), accounts: vec![], instruction_data: "Invalid destination pubkey".to_string() } }),
-    };
+    let _span = info_span!(
+        "mint_token",
+        program_id = %resolve_token_program(payload.token_program),
+        instruction_type = InstructionType::MintTo.as_str(),
+        %mint,
+        %destination,
+        %authority,
+    )
+    .entered();
 
-    let authority = match Pubkey::from_str(&payload.authority) {
-        Ok(p) => p,
-        Err(_) => return Json(ApiResponse { success: false, data: TokenInstructionResponse { program_id: "".to_string(), accounts: vec![], instruction_data: "Invalid authority pubkey".to_string() } }),
-    };
+    if query.dry_run {
+        return Ok(format.negotiate(BuildOutcome::validated()));
+    }
 
-    let ix = match mint_to(
-        &spl_token::id(),
+    let ix = mint_to(
+        &resolve_token_program(payload.token_program),
         &mint,
         &destination,
         &authority,
         &[],
         payload.amount,
-    ) {    Ok(ix) => ix,
-        Err(e) => return Json(ApiResponse { success: false, data: TokenInstructionResponse { program_id: "".to_string(), accounts: vec![], instruction_data: e.to_string() } }),
+    )
+    .map_err(|e| AppError::new(ErrorCode::InstructionBuildFailed, e.to_string()))?;
+
+    let response = with_compiled(
+        instruction_to_response(&ix, InstructionType::MintTo, Encoding::Base64),
+        &compiled,
+        &[ix],
+        None,
+    );
+    Ok(format.negotiate(BuildOutcome::Built(response)))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InitializeAccountRequest {
+    pub account: String,
+    pub mint: String,
+    pub owner: String,
+    /// Use `InitializeAccount3`, which takes the owner as instruction data and
+    /// does not need the rent sysvar account.
+    #[serde(default)]
+    pub initialize_account3: bool,
+    /// `token` or `token-2022`; defaults to the server's `DEFAULT_TOKEN_PROGRAM`.
+    #[serde(default)]
+    pub token_program: Option<TokenProgram>,
+}
+
+/// Initializes an explicitly created (non-associated) token account.
+pub async fn initialize_token_account(
+    State(_): State<AppState>,
+    format: ResponseFormat,
+    Query(query): Query<DryRunQuery>,
+    Query(compiled): Query<CompiledQuery>,
+    Json(payload): Json<InitializeAccountRequest>,
+) -> Result<Negotiated<BuildOutcome<WithCompiled<TokenInstructionResponse>>>, AppError> {
+    let account = parse_pubkey(&payload.account, "account")?;
+    let mint = parse_mint(&payload.mint)?;
+    let owner = parse_pubkey(&payload.owner, "owner")?;
+
+    let _span = info_span!(
+        "initialize_token_account",
+        program_id = %resolve_token_program(payload.token_program),
+        instruction_type = if payload.initialize_account3 {
+            InstructionType::InitializeAccount3
+        } else {
+            InstructionType::InitializeAccount
+        }
+        .as_str(),
+        %account,
+        %mint,
+        %owner,
+    )
+    .entered();
+
+    if query.dry_run {
+        return Ok(format.negotiate(BuildOutcome::validated()));
+    }
+
+    let token_program = resolve_token_program(payload.token_program);
+    let (ix, instruction_type) = if payload.initialize_account3 {
+        (
+            initialize_account3(&token_program, &account, &mint, &owner),
+            InstructionType::InitializeAccount3,
+        )
+    } else {
+        (
+            initialize_account(&token_program, &account, &mint, &owner),
+            InstructionType::InitializeAccount,
+        )
+    };
+    let ix = ix.map_err(|e| AppError::new(ErrorCode::InstructionBuildFailed, e.to_string()))?;
+
+    let response = with_compiled(
+        instruction_to_response(&ix, instruction_type, Encoding::Base64),
+        &compiled,
+        &[ix],
+        None,
+    );
+    Ok(format.negotiate(BuildOutcome::Built(response)))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BurnAndCloseRequest {
+    pub mint: String,
+    pub account: String,
+    pub owner: String,
+    pub amount: u64,
+    pub decimals: u8,
+    pub rent_destination: String,
+    /// `token` or `token-2022`; defaults to the server's `DEFAULT_TOKEN_PROGRAM`.
+    #[serde(default)]
+    pub token_program: Option<TokenProgram>,
+}
+
+/// Burns the remaining balance and closes the account, returning
+/// `[burn_checked, close_account]` in that order.
+pub async fn burn_and_close(
+    State(_): State<AppState>,
+    format: ResponseFormat,
+    Query(query): Query<DryRunQuery>,
+    Query(compiled): Query<CompiledQuery>,
+    Json(payload): Json<BurnAndCloseRequest>,
+) -> Result<Negotiated<BuildOutcome<WithCompiled<InstructionSequence>>>, AppError> {
+    let mint = parse_mint(&payload.mint)?;
+    let account = parse_pubkey(&payload.account, "account")?;
+    let owner = parse_pubkey(&payload.owner, "owner")?;
+    let rent_destination = parse_pubkey(&payload.rent_destination, "rentDestination")?;
+
+    if payload.amount == 0 {
+        return Err(AppError::new(
+            ErrorCode::AmountZero,
+            "Amount is zero, so there is nothing to burn; close the account directly",
+        ));
+    }
+
+    let _span = info_span!(
+        "burn_and_close",
+        program_id = %resolve_token_program(payload.token_program),
+        instruction_type = InstructionType::BurnChecked.as_str(),
+        %mint,
+        %account,
+        %owner,
+    )
+    .entered();
+
+    if query.dry_run {
+        return Ok(format.negotiate(BuildOutcome::validated()));
+    }
+
+    let token_program = resolve_token_program(payload.token_program);
+    let burn = burn_checked(
+        &token_program,
+        &account,
+        &mint,
+        &owner,
+        &[],
+        payload.amount,
+        payload.decimals,
+    )
+    .map_err(|e| AppError::new(ErrorCode::InstructionBuildFailed, e.to_string()))?;
+    let close = close_account(&token_program, &account, &rent_destination, &owner, &[])
+        .map_err(|e| AppError::new(ErrorCode::InstructionBuildFailed, e.to_string()))?;
+
+    let response = InstructionSequence::new([
+        (InstructionType::BurnChecked, &burn),
+        (InstructionType::CloseAccount, &close),
+    ]);
+    let response = with_compiled(
+        response,
+        &compiled,
+        &[burn, close],
+        None,
+    );
+    Ok(format.negotiate(BuildOutcome::Built(response)))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateAndMintRequest {
+    pub mint_authority: String,
+    pub mint: String,
+    pub decimals: u8,
+    pub recipient: String,
+    pub amount: u64,
+    /// Funds the mint account and the recipient's ATA. Defaults to `mintAuthority`.
+    #[serde(default)]
+    pub payer: Option<String>,
+    /// `token` or `token-2022`; defaults to the server's `DEFAULT_TOKEN_PROGRAM`.
+    #[serde(default)]
+    pub token_program: Option<TokenProgram>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateAndMintResponse {
+    #[serde(flatten)]
+    pub sequence: InstructionSequence,
+    pub associated_token_account: String,
+    /// Every key that must sign the transaction: the payer, the new mint
+    /// account (for `create_account`) and the mint authority (for `mint_to`).
+    pub required_signers: Vec<String>,
+}
+
+/// Creates a mint, the recipient's ATA and the initial supply in one call:
+/// `[create_account, initialize_mint, create_associated_token_account, mint_to]`.
+pub async fn create_and_mint(
+    State(_): State<AppState>,
+    Query(query): Query<DryRunQuery>,
+    Query(compiled): Query<CompiledQuery>,
+    Json(payload): Json<CreateAndMintRequest>,
+) -> HandlerResult<BuildOutcome<WithCompiled<CreateAndMintResponse>>> {
+    let mint = parse_mint(&payload.mint)?;
+    let mint_authority = parse_pubkey(&payload.mint_authority, "mintAuthority")?;
+    let recipient = parse_pubkey(&payload.recipient, "recipient")?;
+    let payer = match &payload.payer {
+        Some(payer) => parse_pubkey(payer, "payer")?,
+        None => mint_authority,
+    };
+    reject_default_pubkey(&mint, "mint")?;
+    reject_default_pubkey(&mint_authority, "mintAuthority")?;
+
+    let _span = info_span!(
+        "create_and_mint",
+        program_id = %resolve_token_program(payload.token_program),
+        instruction_type = InstructionType::InitializeMint.as_str(),
+        %mint,
+        %mint_authority,
+        %recipient,
+    )
+    .entered();
+
+    if query.dry_run {
+        return Ok(ApiResponse::from(BuildOutcome::validated()));
+    }
+
+    let token_program = resolve_token_program(payload.token_program);
+    let ata = get_associated_token_address_with_program_id(&recipient, &mint, &token_program);
+
+    let create = create_mint_account(&payer, &mint, &token_program, &[])?;
+    let initialize = initialize_mint(
+        &token_program,
+        &mint,
+        &mint_authority,
+        None,
+        payload.decimals,
+    )
+    .map_err(|e| AppError::new(ErrorCode::InstructionBuildFailed, e.to_string()))?;
+    let create_ata = create_associated_token_account(&payer, &recipient, &mint, &token_program);
+    let mint_ix = mint_to(
+        &token_program,
+        &mint,
+        &ata,
+        &mint_authority,
+        &[],
+        payload.amount,
+    )
+    .map_err(|e| AppError::new(ErrorCode::InstructionBuildFailed, e.to_string()))?;
+
+    let mut required_signers = vec![payer.to_string(), mint.to_string()];
+    if mint_authority != payer {
+        required_signers.push(mint_authority.to_string());
+    }
+
+    let response = CreateAndMintResponse {
+        sequence: InstructionSequence::new([
+            (InstructionType::SystemCreateAccount, &create),
+            (InstructionType::InitializeMint, &initialize),
+            (InstructionType::CreateAssociatedTokenAccount, &create_ata),
+            (InstructionType::MintTo, &mint_ix),
+        ]),
+        associated_token_account: ata.to_string(),
+        required_signers,
     };
 
-    let accounts: Vec<AccountMeta> = ix.accounts.iter().map(|meta| AccountMeta {
-        pubkey: meta.pubkey.to_string(),
-        is_signer: meta.is_signer,
-        is_writable: meta.is_writable,
-    }).collect();
-
-    Json(ApiResponse {
-        success: true,
-        data: TokenInstructionResponse {
-            program_id: ix.program_id.to_string(),
-            accounts,
-            instruction_data: base64::encode(ix.data),
+    Ok(ApiResponse::from(BuildOutcome::Built(with_compiled(
+        response,
+        &compiled,
+        &[create, initialize, create_ata, mint_ix],
+        Some(&payer),
+    ))))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApproveRequest {
+    pub source: String,
+    pub delegate: String,
+    pub owner: String,
+    pub amount: u64,
+    /// `approve_checked` (the default), which also pins the mint and decimals.
+    /// `false` builds plain `approve` for older tooling.
+    #[serde(default = "default_checked")]
+    pub checked: bool,
+    /// Required when `checked` is true; ignored otherwise.
+    #[serde(default)]
+    pub mint: Option<String>,
+    /// Required when `checked` is true; ignored otherwise.
+    #[serde(default)]
+    pub decimals: Option<u8>,
+    /// `token` or `token-2022`; defaults to the server's `DEFAULT_TOKEN_PROGRAM`.
+    #[serde(default)]
+    pub token_program: Option<TokenProgram>,
+}
+
+pub(crate) fn default_checked() -> bool {
+    true
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApproveResponse {
+    #[serde(flatten)]
+    pub instruction: TokenInstructionResponse,
+    /// `approve_checked` or `approve`.
+    pub variant: &'static str,
+}
+
+impl ToProto for ApproveResponse {
+    fn encode_proto(&self) -> Option<Vec<u8>> {
+        self.instruction.encode_proto()
+    }
+}
+
+/// Lets `delegate` transfer up to `amount` from `source` on the owner's behalf.
+pub async fn approve_token(
+    State(_): State<AppState>,
+    format: ResponseFormat,
+    Query(query): Query<DryRunQuery>,
+    Query(compiled): Query<CompiledQuery>,
+    Json(payload): Json<ApproveRequest>,
+) -> Result<Negotiated<BuildOutcome<WithCompiled<ApproveResponse>>>, AppError> {
+    let source = parse_pubkey(&payload.source, "source")?;
+    let delegate = parse_pubkey(&payload.delegate, "delegate")?;
+    let owner = parse_pubkey(&payload.owner, "owner")?;
+    let mint_and_decimals = if payload.checked {
+        let mint = payload.mint.as_deref().ok_or_else(|| {
+            AppError::new(ErrorCode::InvalidPubkey, "mint is required when checked is true")
+        })?;
+        let decimals = payload.decimals.ok_or_else(|| {
+            AppError::new(ErrorCode::InvalidAmount, "decimals is required when checked is true")
+        })?;
+        Some((parse_mint(mint)?, decimals))
+    } else {
+        None
+    };
+
+    let _span = info_span!(
+        "approve_token",
+        program_id = %resolve_token_program(payload.token_program),
+        instruction_type = if payload.checked {
+            InstructionType::ApproveChecked
+        } else {
+            InstructionType::Approve
+        }
+        .as_str(),
+        %source,
+        %delegate,
+        %owner,
+    )
+    .entered();
+
+    if query.dry_run {
+        return Ok(format.negotiate(BuildOutcome::validated()));
+    }
+
+    let token_program = resolve_token_program(payload.token_program);
+    let (ix, instruction_type, variant) = match mint_and_decimals {
+        Some((mint, decimals)) => (
+            approve_checked(
+                &token_program,
+                &source,
+                &mint,
+                &delegate,
+                &owner,
+                &[],
+                payload.amount,
+                decimals,
+            ),
+            InstructionType::ApproveChecked,
+            "approve_checked",
+        ),
+        None => (
+            approve(&token_program, &source, &delegate, &owner, &[], payload.amount),
+            InstructionType::Approve,
+            "approve",
+        ),
+    };
+    let ix = ix.map_err(|e| AppError::new(ErrorCode::InstructionBuildFailed, e.to_string()))?;
+
+    let response = with_compiled(
+        ApproveResponse {
+            instruction: instruction_to_response(&ix, instruction_type, Encoding::Base64),
+            variant,
         },
-    })
+        &compiled,
+        &[ix],
+        None,
+    );
+    Ok(format.negotiate(BuildOutcome::Built(response)))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetTransferFeeRequest {
+    pub mint: String,
+    /// The mint's transfer-fee config authority.
+    pub authority: String,
+    /// Fee per transfer in hundredths of a percent; at most 10000 (100%).
+    pub transfer_fee_basis_points: u16,
+    /// Cap on the fee of a single transfer, in raw token units.
+    pub maximum_fee: u64,
+}
+
+/// Updates the fee of a Token-2022 mint with the transfer-fee extension. The
+/// new fee takes effect two epochs later, as the program schedules it.
+pub async fn set_transfer_fee(
+    State(_): State<AppState>,
+    format: ResponseFormat,
+    Query(query): Query<DryRunQuery>,
+    Query(compiled): Query<CompiledQuery>,
+    Json(payload): Json<SetTransferFeeRequest>,
+) -> Result<Negotiated<BuildOutcome<WithCompiled<TokenInstructionResponse>>>, AppError> {
+    let mint = parse_mint(&payload.mint)?;
+    let authority = parse_pubkey(&payload.authority, "authority")?;
+
+    if payload.transfer_fee_basis_points > MAX_FEE_BASIS_POINTS {
+        return Err(AppError::new(
+            ErrorCode::InvalidAmount,
+            format!(
+                "transferFeeBasisPoints is {}; the maximum is {} (100%)",
+                payload.transfer_fee_basis_points, MAX_FEE_BASIS_POINTS
+            ),
+        ));
+    }
+
+    let _span = info_span!(
+        "set_transfer_fee",
+        program_id = %spl_token_2022::id(),
+        instruction_type = InstructionType::SetTransferFee.as_str(),
+        %mint,
+        %authority,
+    )
+    .entered();
+
+    if query.dry_run {
+        return Ok(format.negotiate(BuildOutcome::validated()));
+    }
+
+    let ix = transfer_fee::instruction::set_transfer_fee(
+        &spl_token_2022::id(),
+        &mint,
+        &authority,
+        &[],
+        payload.transfer_fee_basis_points,
+        payload.maximum_fee,
+    )
+    .map_err(|e| AppError::new(ErrorCode::InstructionBuildFailed, e.to_string()))?;
+
+    let response = with_compiled(
+        instruction_to_response(&ix, InstructionType::SetTransferFee, Encoding::Base64),
+        &compiled,
+        &[ix],
+        None,
+    );
+    Ok(format.negotiate(BuildOutcome::Built(response)))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateMultisigRequest {
+    /// The (already allocated) multisig account to initialize.
+    pub account: String,
+    pub signers: Vec<String>,
+    /// Signatures required out of `signers`.
+    pub m: u8,
+    /// `token` or `token-2022`; defaults to the server's `DEFAULT_TOKEN_PROGRAM`.
+    #[serde(default)]
+    pub token_program: Option<TokenProgram>,
+}
+
+/// Initializes an M-of-N multisig that can then be used as a mint, freeze or
+/// account authority.
+pub async fn create_multisig(
+    State(_): State<AppState>,
+    format: ResponseFormat,
+    Query(query): Query<DryRunQuery>,
+    Query(compiled): Query<CompiledQuery>,
+    Json(payload): Json<CreateMultisigRequest>,
+) -> Result<Negotiated<BuildOutcome<WithCompiled<TokenInstructionResponse>>>, AppError> {
+    let account = parse_pubkey(&payload.account, "account")?;
+    let signers = payload
+        .signers
+        .iter()
+        .enumerate()
+        .map(|(i, signer)| parse_pubkey(signer, &format!("signers[{}]", i)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let n = signers.len();
+    if !(MIN_SIGNERS..=MAX_SIGNERS).contains(&n) {
+        return Err(AppError::new(
+            ErrorCode::InvalidMultisig,
+            format!("A multisig needs {} to {} signers; got {}", MIN_SIGNERS, MAX_SIGNERS, n),
+        ));
+    }
+    if payload.m == 0 || payload.m as usize > n {
+        return Err(AppError::new(
+            ErrorCode::InvalidMultisig,
+            format!("m must be between 1 and the number of signers ({}); got {}", n, payload.m),
+        ));
+    }
+
+    let _span = info_span!(
+        "create_multisig",
+        program_id = %resolve_token_program(payload.token_program),
+        instruction_type = InstructionType::InitializeMultisig.as_str(),
+        %account,
+        signers = n,
+        m = payload.m,
+    )
+    .entered();
+
+    if query.dry_run {
+        return Ok(format.negotiate(BuildOutcome::validated()));
+    }
+
+    let ix = initialize_multisig(
+        &resolve_token_program(payload.token_program),
+        &account,
+        &signers.iter().collect::<Vec<_>>(),
+        payload.m,
+    )
+    .map_err(|e| AppError::new(ErrorCode::InstructionBuildFailed, e.to_string()))?;
+
+    let response = with_compiled(
+        instruction_to_response(&ix, InstructionType::InitializeMultisig, Encoding::Base64),
+        &compiled,
+        &[ix],
+        None,
+    );
+    Ok(format.negotiate(BuildOutcome::Built(response)))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountRentRequest {
+    /// Token-2022 account extensions, e.g. `immutableOwner` or
+    /// `transferFeeAmount`.
+    #[serde(default)]
+    pub extensions: Vec<ExtensionType>,
+    /// `token` or `token-2022`; defaults to the server's `DEFAULT_TOKEN_PROGRAM`.
+    #[serde(default)]
+    pub token_program: Option<TokenProgram>,
+    /// Ask the cluster for the minimum instead of using the default (mainnet)
+    /// rent parameters. Needs `RPC_URL`.
+    #[serde(default)]
+    pub rpc: bool,
+    /// Deadline for the RPC call when `rpc` is set, in milliseconds; clamped
+    /// to `MAX_RPC_TIMEOUT_MS`.
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountRentData {
+    /// Account size in bytes, including any extensions.
+    pub space: usize,
+    /// Rent-exempt minimum balance for `space`.
+    pub lamports: u64,
+    /// `rpc` or `default`: where the rent parameters came from.
+    pub source: &'static str,
+}
+
+/// Rent-exempt minimum for a token account, so clients can fund
+/// `/token/initialize-account` or an ATA correctly. Token-2022 extensions make
+/// the account larger than `Account::LEN`.
+pub async fn account_rent(
+    State(state): State<AppState>,
+    Json(payload): Json<AccountRentRequest>,
+) -> HandlerResult<AccountRentData> {
+    if !payload.extensions.is_empty()
+        && resolve_token_program(payload.token_program) != spl_token_2022::id()
+    {
+        return Err(AppError::new(
+            ErrorCode::UnsupportedExtension,
+            "extensions need tokenProgram token-2022",
+        ));
+    }
+    if let Some(extension) = payload
+        .extensions
+        .iter()
+        .find(|extension| extension.get_account_type() != AccountType::Account)
+    {
+        return Err(AppError::new(
+            ErrorCode::UnsupportedExtension,
+            format!("{:?} is not a token account extension", extension),
+        ));
+    }
+
+    let space = ExtensionType::try_calculate_account_len::<Account>(&payload.extensions)
+        .map_err(|e| AppError::new(ErrorCode::UnsupportedExtension, e.to_string()))?;
+
+    let (lamports, source) = if payload.rpc {
+        let rpc = state.rpc()?;
+        let lamports = within(
+            payload.timeout_ms,
+            rpc.get_minimum_balance_for_rent_exemption(space),
+        )
+        .await?
+        .map_err(rpc_error)?;
+        (lamports, "rpc")
+    } else {
+        (Rent::default().minimum_balance(space), "default")
+    };
+
+    Ok(ApiResponse::from(AccountRentData {
+        space,
+        lamports,
+        source,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FreezeAllRequest {
+    pub mint: String,
+    pub freeze_authority: String,
+    /// Token accounts of `mint` to freeze.
+    pub accounts: Vec<String>,
+    /// `token` or `token-2022`; defaults to the server's `DEFAULT_TOKEN_PROGRAM`.
+    #[serde(default)]
+    pub token_program: Option<TokenProgram>,
+}
+
+/// One `freeze_account` per entry of `accounts`, in the same order, for a
+/// freeze authority that needs to lock many accounts at once.
+pub async fn freeze_all(
+    State(_): State<AppState>,
+    format: ResponseFormat,
+    Query(query): Query<DryRunQuery>,
+    Query(compiled): Query<CompiledQuery>,
+    Json(payload): Json<FreezeAllRequest>,
+) -> Result<Negotiated<BuildOutcome<WithCompiled<InstructionSequence>>>, AppError> {
+    ensure_batch_size(payload.accounts.len())?;
+    let mint = parse_mint(&payload.mint)?;
+    let freeze_authority = parse_pubkey(&payload.freeze_authority, "freezeAuthority")?;
+    let accounts = payload
+        .accounts
+        .iter()
+        .enumerate()
+        .map(|(i, account)| parse_pubkey(account, &format!("accounts[{}]", i)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let _span = info_span!(
+        "freeze_all",
+        program_id = %resolve_token_program(payload.token_program),
+        instruction_type = InstructionType::FreezeAccount.as_str(),
+        %mint,
+        %freeze_authority,
+        accounts = accounts.len(),
+    )
+    .entered();
+
+    if query.dry_run {
+        return Ok(format.negotiate(BuildOutcome::validated()));
+    }
+
+    let token_program = resolve_token_program(payload.token_program);
+    let instructions = accounts
+        .iter()
+        .map(|account| {
+            freeze_account(&token_program, account, &mint, &freeze_authority, &[])
+                .map_err(|e| AppError::new(ErrorCode::InstructionBuildFailed, e.to_string()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let response = with_compiled(
+        InstructionSequence::new(
+            instructions.iter().map(|ix| (InstructionType::FreezeAccount, ix)),
+        ),
+        &compiled,
+        &instructions,
+        None,
+    );
+    Ok(format.negotiate(BuildOutcome::Built(response)))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AtaQuery {
+    pub owner: String,
+    pub mint: String,
+    /// `token` or `token-2022`; defaults to the server's `DEFAULT_TOKEN_PROGRAM`.
+    #[serde(default)]
+    pub token_program: Option<TokenProgram>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AtaData {
+    pub address: String,
+    pub bump: u8,
+}
+
+/// Derives the associated token address of `owner` for `mint`, with its bump
+/// seed. Nothing is built or fetched.
+pub async fn associated_token_address(
+    State(_): State<AppState>,
+    Query(query): Query<AtaQuery>,
+) -> HandlerResult<AtaData> {
+    let owner = parse_pubkey(&query.owner, "owner")?;
+    let mint = parse_pubkey(&query.mint, "mint")?;
+    let token_program = resolve_token_program(query.token_program);
+
+    // The seeds `get_associated_token_address_with_program_id` uses; the
+    // crate keeps its bump-returning variant private.
+    let (address, bump) = Pubkey::find_program_address(
+        &[owner.as_ref(), token_program.as_ref(), mint.as_ref()],
+        &spl_associated_token_account::id(),
+    );
+    Ok(ApiResponse::from(AtaData {
+        address: address.to_string(),
+        bump,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+    use serde_json::json;
+    use solana_program::{
+        program_pack::Pack, system_instruction::SystemInstruction, system_program,
+    };
+
+    use super::*;
+
+    async fn create(body: serde_json::Value) -> Result<serde_json::Value, AppError> {
+        let request: CreateTokenRequest = serde_json::from_value(body).unwrap();
+        let response = create_token(
+            State(AppState::for_tests()),
+            ResponseFormat::Json,
+            Query(DryRunQuery::default()),
+            Query(CompiledQuery::default()),
+            Json(request),
+        )
+        .await?;
+        Ok(json_data(response))
+    }
+
+    fn json_data<T: Serialize>(response: Negotiated<T>) -> serde_json::Value {
+        let Negotiated::Json(response) = response else {
+            panic!("expected a JSON response");
+        };
+        serde_json::to_value(response.into_data()).unwrap()
+    }
+
+    /// Checks a sequence's `steps` and each instruction's program, in order.
+    fn assert_sequence(data: &serde_json::Value, expected: &[(&str, Pubkey)]) {
+        let steps: Vec<&str> = expected.iter().map(|(step, _)| *step).collect();
+        assert_eq!(data["steps"], json!(steps));
+        let instructions = data["instructions"].as_array().unwrap();
+        assert_eq!(instructions.len(), expected.len());
+        for (ix, (step, program_id)) in instructions.iter().zip(expected) {
+            assert_eq!(ix["instructionType"], *step);
+            assert_eq!(ix["program_id"], program_id.to_string(), "{}", step);
+        }
+    }
+
+    #[tokio::test]
+    async fn create_token_rejects_default_pubkeys() {
+        let zero = Pubkey::default().to_string();
+        let key = Pubkey::new_unique().to_string();
+        for (mint, mint_authority, field) in
+            [(&zero, &key, "mint"), (&key, &zero, "mintAuthority")]
+        {
+            let err = create(json!({
+                "mint": mint,
+                "mintAuthority": mint_authority,
+                "decimals": 6,
+            }))
+            .await
+            .unwrap_err();
+            assert_eq!(err.code, ErrorCode::DefaultPubkey);
+            assert!(err.message.starts_with(field), "{}", err.message);
+        }
+    }
+
+    #[tokio::test]
+    async fn create_token_allows_default_pubkeys_when_asked() {
+        let zero = Pubkey::default().to_string();
+        create(json!({
+            "mint": zero,
+            "mintAuthority": zero,
+            "decimals": 6,
+            "allowDefaultPubkey": true,
+        }))
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn create_token_rejects_mint_with_generate_mint() {
+        let err = create(json!({
+            "mint": Pubkey::new_unique().to_string(),
+            "mintAuthority": Pubkey::new_unique().to_string(),
+            "decimals": 6,
+            "generateMint": true,
+        }))
+        .await
+        .unwrap_err();
+        assert_eq!(err.code, ErrorCode::ConflictingFields);
+        assert_eq!(err.code.status(), axum::http::StatusCode::BAD_REQUEST);
+    }
+
+    fn extension_request(create_account: bool) -> serde_json::Value {
+        json!({
+            "mint": Pubkey::new_unique().to_string(),
+            "mintAuthority": Pubkey::new_unique().to_string(),
+            "decimals": 6,
+            "tokenProgram": "token-2022",
+            "closeAuthority": Pubkey::new_unique().to_string(),
+            "createAccount": create_account,
+        })
+    }
+
+    #[tokio::test]
+    async fn create_token_extensions_need_create_account_for_given_mint() {
+        let err = create(extension_request(false)).await.unwrap_err();
+        assert_eq!(err.code, ErrorCode::UnsupportedExtension);
+    }
+
+    #[tokio::test]
+    async fn create_token_sizes_given_mint_for_extensions() {
+        let data = create(extension_request(true)).await.unwrap();
+        assert_sequence(
+            &data,
+            &[
+                ("systemCreateAccount", system_program::id()),
+                ("initializeMintCloseAuthority", spl_token_2022::id()),
+                ("initializeMint", spl_token_2022::id()),
+            ],
+        );
+        let create_account = data["instructions"][0]["instruction_data"].as_str().unwrap();
+        let create_account = STANDARD.decode(create_account).unwrap();
+        let space = mint_space(&[ExtensionType::MintCloseAuthority]).unwrap();
+        assert!(space > Mint::LEN);
+        match bincode::deserialize(&create_account).unwrap() {
+            SystemInstruction::CreateAccount { space: got, .. } => {
+                assert_eq!(got, space as u64)
+            }
+            other => panic!("expected CreateAccount, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn create_token_orders_every_extension_before_initialize_mint() {
+        let mut request = extension_request(true);
+        request["transferHookProgramId"] = Pubkey::new_unique().to_string().into();
+        let data = create(request).await.unwrap();
+        assert_sequence(
+            &data,
+            &[
+                ("systemCreateAccount", system_program::id()),
+                ("initializeMintCloseAuthority", spl_token_2022::id()),
+                ("initializeTransferHook", spl_token_2022::id()),
+                ("initializeMint", spl_token_2022::id()),
+            ],
+        );
+    }
+
+    #[tokio::test]
+    async fn create_and_mint_sequence() {
+        let request: CreateAndMintRequest = serde_json::from_value(json!({
+            "mintAuthority": Pubkey::new_unique().to_string(),
+            "mint": Pubkey::new_unique().to_string(),
+            "decimals": 6,
+            "recipient": Pubkey::new_unique().to_string(),
+            "amount": 1_000,
+            "tokenProgram": "token-2022",
+        }))
+        .unwrap();
+        let response = create_and_mint(
+            State(AppState::for_tests()),
+            Query(DryRunQuery::default()),
+            Query(CompiledQuery::default()),
+            Json(request),
+        )
+        .await
+        .unwrap();
+        let data = serde_json::to_value(response.into_data()).unwrap();
+        assert_sequence(
+            &data,
+            &[
+                ("systemCreateAccount", system_program::id()),
+                ("initializeMint", spl_token_2022::id()),
+                ("createAssociatedTokenAccount", spl_associated_token_account::id()),
+                ("mintTo", spl_token_2022::id()),
+            ],
+        );
+    }
+
+    #[tokio::test]
+    async fn burn_and_close_sequence() {
+        let request: BurnAndCloseRequest = serde_json::from_value(json!({
+            "mint": Pubkey::new_unique().to_string(),
+            "account": Pubkey::new_unique().to_string(),
+            "owner": Pubkey::new_unique().to_string(),
+            "amount": 5,
+            "decimals": 6,
+            "rentDestination": Pubkey::new_unique().to_string(),
+            "tokenProgram": "token",
+        }))
+        .unwrap();
+        let response = burn_and_close(
+            State(AppState::for_tests()),
+            ResponseFormat::Json,
+            Query(DryRunQuery::default()),
+            Query(CompiledQuery::default()),
+            Json(request),
+        )
+        .await
+        .unwrap();
+        assert_sequence(
+            &json_data(response),
+            &[("burnChecked", spl_token::id()), ("closeAccount", spl_token::id())],
+        );
+    }
+
+    #[tokio::test]
+    async fn freeze_all_sequence() {
+        let request: FreezeAllRequest = serde_json::from_value(json!({
+            "mint": Pubkey::new_unique().to_string(),
+            "freezeAuthority": Pubkey::new_unique().to_string(),
+            "accounts": [Pubkey::new_unique().to_string(), Pubkey::new_unique().to_string()],
+            "tokenProgram": "token",
+        }))
+        .unwrap();
+        let response = freeze_all(
+            State(AppState::for_tests()),
+            ResponseFormat::Json,
+            Query(DryRunQuery::default()),
+            Query(CompiledQuery::default()),
+            Json(request),
+        )
+        .await
+        .unwrap();
+        assert_sequence(
+            &json_data(response),
+            &[("freezeAccount", spl_token::id()), ("freezeAccount", spl_token::id())],
+        );
+    }
 }
@@ -0,0 +1,813 @@
+use axum::{extract::State, http::HeaderMap, Json};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use solana_client::nonce_utils::nonblocking::data_from_account;
+use solana_program::{
+    hash::Hash,
+    instruction::{CompiledInstruction, Instruction},
+    message::Message,
+    program_utils::limited_deserialize,
+    pubkey::Pubkey,
+    system_instruction::{self, SystemInstruction},
+    system_program,
+};
+use solana_sdk::{
+    packet::PACKET_DATA_SIZE,
+    sanitize::Sanitize,
+    signature::{Keypair, Signature, Signer},
+    transaction::Transaction,
+};
+use spl_token_2022::instruction::TokenInstruction;
+use tracing::info_span;
+
+use super::instructions::{spec_instructions, InstructionSpec};
+use crate::config;
+use crate::error::{AppError, ErrorCode};
+use crate::idempotency::{idempotency_key, Claim};
+use crate::keys::signing_secret;
+use crate::response::{ApiResponse, HandlerResult};
+use crate::rpc::{fetch_account, requested_timeout, rpc_error, within};
+use crate::state::AppState;
+use crate::types::{
+    decode_secret, ensure_batch_size, parse_pubkey, AccountMeta, TokenInstructionResponse,
+};
+
+/// ------------------ /transaction/inspect ------------------
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InspectTransactionRequest {
+    /// Base64 of a bincode-serialized legacy `Transaction`.
+    pub transaction: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InspectTransactionData {
+    /// `None` only for a message with no account keys.
+    pub fee_payer: Option<String>,
+    pub recent_blockhash: String,
+    pub signature_count: usize,
+    pub required_signatures: u8,
+    pub instructions: Vec<InspectedInstruction>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InspectedInstruction {
+    #[serde(flatten)]
+    pub instruction: TokenInstructionResponse,
+    /// Instruction data decoded for the programs this server knows about
+    /// (system, SPL Token, Token-2022 and Memo); absent otherwise.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decoded: Option<Value>,
+}
+
+/// Deserializes a wire transaction and lists what it contains, for debugging.
+pub async fn inspect_transaction(
+    State(_): State<AppState>,
+    Json(payload): Json<InspectTransactionRequest>,
+) -> HandlerResult<InspectTransactionData> {
+    let transaction = decode_transaction(&payload.transaction)?;
+    let message = &transaction.message;
+
+    let instructions = message
+        .instructions
+        .iter()
+        .enumerate()
+        .map(|(i, ix)| {
+            inspect_instruction(message, ix).map_err(|e| {
+                AppError::new(e.code, format!("instructions[{}]: {}", i, e.message))
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(ApiResponse::from(InspectTransactionData {
+        fee_payer: message.account_keys.first().map(ToString::to_string),
+        recent_blockhash: message.recent_blockhash.to_string(),
+        signature_count: transaction.signatures.len(),
+        required_signatures: message.header.num_required_signatures,
+        instructions,
+    }))
+}
+
+/// Base64 of a bincode-serialized legacy `Transaction`, at most
+/// `MAX_TRANSACTION_BYTES` once decoded, and structurally sound: every
+/// instruction's indices are in bounds and there is a signature slot for each
+/// required signer.
+fn decode_transaction(encoded: &str) -> Result<Transaction, AppError> {
+    let encoded = encoded.trim();
+    let max = config::get().max_transaction_bytes;
+    let too_large = || {
+        AppError::new(
+            ErrorCode::TransactionTooLarge,
+            format!("Transaction exceeds the limit of {} bytes", max),
+        )
+    };
+    // The estimate can overshoot by the two bytes of padding, so it only
+    // screens out input that is clearly too large before decoding it.
+    if base64::decoded_len_estimate(encoded.len()) > max + 2 {
+        return Err(too_large());
+    }
+
+    let bytes = STANDARD
+        .decode(encoded)
+        .map_err(|_| AppError::new(ErrorCode::InvalidTransaction, "Invalid base64 transaction"))?;
+    if bytes.len() > max {
+        return Err(too_large());
+    }
+    let transaction: Transaction = limited_deserialize(&bytes, max as u64).map_err(|_| {
+        AppError::new(ErrorCode::InvalidTransaction, "Failed to deserialize transaction")
+    })?;
+    transaction.sanitize().map_err(|e| {
+        AppError::new(ErrorCode::InvalidTransaction, format!("Malformed transaction: {}", e))
+    })?;
+    Ok(transaction)
+}
+
+/// Rejects a transaction whose signature list isn't exactly one slot per
+/// required signer, which `sanitize` alone allows; handlers that index or
+/// fill the slots rely on it.
+fn ensure_signature_slots(transaction: &Transaction) -> Result<(), AppError> {
+    let required = usize::from(transaction.message.header.num_required_signatures);
+    if transaction.signatures.len() != required {
+        return Err(AppError::new(
+            ErrorCode::InvalidTransaction,
+            "Signature count doesn't match the message's required signers",
+        ));
+    }
+    Ok(())
+}
+
+fn inspect_instruction(
+    message: &Message,
+    ix: &CompiledInstruction,
+) -> Result<InspectedInstruction, AppError> {
+    let key = |index: u8| {
+        message.account_keys.get(index as usize).ok_or_else(|| {
+            AppError::new(
+                ErrorCode::InvalidTransaction,
+                format!("account index {} is out of range", index),
+            )
+        })
+    };
+
+    let program_id = key(ix.program_id_index)?;
+    let accounts = ix
+        .accounts
+        .iter()
+        .map(|&index| {
+            Ok(AccountMeta {
+                pubkey: key(index)?.to_string(),
+                is_signer: message.is_signer(index as usize),
+                is_writable: message.is_writable(index as usize),
+            })
+        })
+        .collect::<Result<Vec<_>, AppError>>()?;
+
+    Ok(InspectedInstruction {
+        instruction: TokenInstructionResponse {
+            instruction_type: None,
+            program_id: program_id.to_string(),
+            accounts,
+            instruction_data: STANDARD.encode(&ix.data),
+        },
+        decoded: decode_instruction_data(program_id, &ix.data),
+    })
+}
+
+/// Best-effort decoding of instruction data for well-known programs.
+pub fn decode_instruction_data(program_id: &Pubkey, data: &[u8]) -> Option<Value> {
+    if *program_id == system_program::id() {
+        let ix: SystemInstruction = limited_deserialize(data, PACKET_DATA_SIZE as u64).ok()?;
+        serde_json::to_value(ix).ok()
+    } else if *program_id == spl_token::id() || *program_id == spl_token_2022::id() {
+        let ix = TokenInstruction::unpack(data).ok()?;
+        serde_json::to_value(ix).ok()
+    } else if *program_id == spl_memo::id() || *program_id == spl_memo::v1::id() {
+        let memo = std::str::from_utf8(data).ok()?;
+        Some(json!({ "memo": memo }))
+    } else {
+        None
+    }
+}
+
+/// ------------------ /transaction/build ------------------
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildTransactionRequest {
+    pub fee_payer: String,
+    /// Built in order, as by `/instructions/batch`.
+    pub instructions: Vec<InstructionSpec>,
+    /// Give either this, `fetchBlockhash`, or `nonceAccount` and
+    /// `nonceAuthority`.
+    #[serde(default)]
+    pub recent_blockhash: Option<String>,
+    /// Use the RPC node's latest blockhash instead of `recentBlockhash`.
+    #[serde(default)]
+    pub fetch_blockhash: bool,
+    /// Durable nonce account whose stored nonce replaces the blockhash. Its
+    /// current value is read from the RPC node.
+    #[serde(default)]
+    pub nonce_account: Option<String>,
+    #[serde(default)]
+    pub nonce_authority: Option<String>,
+    /// Deadline for the blockhash or nonce account lookup, in milliseconds;
+    /// clamped to `MAX_RPC_TIMEOUT_MS`.
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    /// Return a transaction over the packet limit instead of rejecting it.
+    /// For testing only: the cluster won't accept it.
+    #[serde(default)]
+    pub allow_oversize: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildTransactionData {
+    /// Base64 of the compiled `Message`: the bytes each signer signs, and
+    /// what `getFeeForMessage` takes.
+    pub message: String,
+    /// Base64 of the bincode-serialized unsigned legacy `Transaction`, which
+    /// wraps the same message.
+    pub transaction: String,
+    /// Serialized transaction size in bytes, signatures included.
+    pub size: usize,
+    /// The blockhash the transaction uses: `recentBlockhash`, the fetched
+    /// one, or the nonce.
+    pub recent_blockhash: String,
+    /// With `fetchBlockhash`, the last block height at which the fetched
+    /// blockhash is still accepted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_valid_block_height: Option<u64>,
+    /// Present for durable-nonce transactions, whose first instruction is
+    /// `advance_nonce_account`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce_account: Option<String>,
+}
+
+/// Where the transaction's blockhash comes from.
+enum BlockhashSource {
+    Recent(Hash),
+    Latest,
+    Nonce { account: Pubkey, authority: Pubkey },
+}
+
+/// Compiles instructions into an unsigned transaction, ready for the signers.
+/// With a durable nonce it stays valid until the nonce is advanced, so it can
+/// be signed offline at leisure.
+pub async fn build_transaction(
+    State(state): State<AppState>,
+    Json(payload): Json<BuildTransactionRequest>,
+) -> HandlerResult<BuildTransactionData> {
+    let fee_payer = parse_pubkey(&payload.fee_payer, "feePayer")?;
+    let source = blockhash_source(&payload)?;
+    let mut instructions = compile_specs(&payload.instructions)?;
+
+    let mut last_valid_block_height = None;
+    let (blockhash, nonce_account) = match source {
+        BlockhashSource::Recent(blockhash) => (blockhash, None),
+        BlockhashSource::Latest => {
+            let rpc = state.rpc()?;
+            let (blockhash, last_valid) = within(
+                payload.timeout_ms,
+                rpc.get_latest_blockhash_with_commitment(rpc.commitment()),
+            )
+            .await?
+            .map_err(rpc_error)?;
+            last_valid_block_height = Some(last_valid);
+            (blockhash, None)
+        }
+        BlockhashSource::Nonce { account, authority } => {
+            let rpc = state.rpc()?;
+            let nonce = within(payload.timeout_ms, fetch_account(&rpc, &account, rpc.commitment()))
+                .await?
+                .map_err(rpc_error)?
+                .ok_or_else(|| {
+                    AppError::new(
+                        ErrorCode::InvalidAccount,
+                        format!("Nonce account {} not found", account),
+                    )
+                })?;
+            let data = data_from_account(&nonce).map_err(|e| {
+                AppError::new(
+                    ErrorCode::InvalidAccount,
+                    format!("{} is not an initialized nonce account: {}", account, e),
+                )
+            })?;
+            if data.authority != authority {
+                return Err(AppError::new(
+                    ErrorCode::InvalidAccount,
+                    format!(
+                        "Nonce authority of {} is {}, not {}",
+                        account, data.authority, authority
+                    ),
+                ));
+            }
+            // The runtime requires the advance to be the first instruction.
+            instructions.insert(0, system_instruction::advance_nonce_account(&account, &authority));
+            (data.blockhash(), Some(account))
+        }
+    };
+
+    let message = Message::new_with_blockhash(&instructions, Some(&fee_payer), &blockhash);
+    let transaction = Transaction::new_unsigned(message);
+    // The unsigned transaction already holds a placeholder per signature, so
+    // this is the size it will have once signed.
+    let bytes = serialize_transaction(&transaction, payload.allow_oversize)?;
+
+    Ok(ApiResponse::from(BuildTransactionData {
+        message: STANDARD.encode(transaction.message.serialize()),
+        size: bytes.len(),
+        transaction: STANDARD.encode(bytes),
+        recent_blockhash: blockhash.to_string(),
+        last_valid_block_height,
+        nonce_account: nonce_account.map(|account| account.to_string()),
+    }))
+}
+
+/// Each spec's instructions, in order, as one list.
+fn compile_specs(specs: &[InstructionSpec]) -> Result<Vec<Instruction>, AppError> {
+    ensure_batch_size(specs.len())?;
+    Ok(specs
+        .iter()
+        .enumerate()
+        .map(|(i, spec)| {
+            spec_instructions(spec).map_err(|mut e| {
+                e.message = format!("instructions[{}]: {}", i, e.message);
+                e
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?
+        .concat())
+}
+
+/// The wire bytes of `transaction`, refused past the packet limit unless
+/// `allow_oversize`.
+fn serialize_transaction(
+    transaction: &Transaction,
+    allow_oversize: bool,
+) -> Result<Vec<u8>, AppError> {
+    let bytes = bincode::serialize(transaction).map_err(|e| {
+        AppError::new(ErrorCode::InternalError, format!("Failed to serialize transaction: {}", e))
+    })?;
+    if bytes.len() > PACKET_DATA_SIZE && !allow_oversize {
+        return Err(AppError::new(
+            ErrorCode::TransactionTooLarge,
+            format!(
+                "Transaction is {} bytes, over the {}-byte packet limit; split the \
+                 instructions across several transactions or use address lookup tables",
+                bytes.len(),
+                PACKET_DATA_SIZE
+            ),
+        ));
+    }
+    Ok(bytes)
+}
+
+fn parse_blockhash(blockhash: &str) -> Result<Hash, AppError> {
+    blockhash
+        .trim()
+        .parse()
+        .map_err(|_| AppError::new(ErrorCode::InvalidBlockhash, "Invalid base58 recentBlockhash"))
+}
+
+fn blockhash_source(payload: &BuildTransactionRequest) -> Result<BlockhashSource, AppError> {
+    match (
+        payload.recent_blockhash.as_deref(),
+        payload.fetch_blockhash,
+        payload.nonce_account.as_deref(),
+        payload.nonce_authority.as_deref(),
+    ) {
+        (Some(blockhash), false, None, None) => {
+            Ok(BlockhashSource::Recent(parse_blockhash(blockhash)?))
+        }
+        (None, true, None, None) => Ok(BlockhashSource::Latest),
+        (None, false, Some(account), Some(authority)) => Ok(BlockhashSource::Nonce {
+            account: parse_pubkey(account, "nonceAccount")?,
+            authority: parse_pubkey(authority, "nonceAuthority")?,
+        }),
+        _ => Err(AppError::new(
+            ErrorCode::InvalidBlockhash,
+            "Provide exactly one of recentBlockhash, fetchBlockhash, or nonceAccount with \
+             nonceAuthority",
+        )),
+    }
+}
+
+/// ------------------ /transaction/sign ------------------
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignTransactionRequest {
+    pub fee_payer: String,
+    /// Built in order, as by `/instructions/batch`.
+    pub instructions: Vec<InstructionSpec>,
+    pub recent_blockhash: String,
+    /// Base58 secret keys, one per required signer, the fee payer included.
+    pub secrets: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignTransactionData {
+    /// Base64 of the fully signed, bincode-serialized legacy `Transaction`.
+    pub transaction: String,
+    /// The fee payer's signature, which is also the transaction id.
+    pub signature: String,
+    pub size: usize,
+}
+
+/// Builds a transaction as `/transaction/build` does and signs it with every
+/// required signer's secret, without touching the network. Nothing derived
+/// from `secrets` other than public keys reaches the logs.
+pub async fn sign_transaction(
+    State(_): State<AppState>,
+    Json(payload): Json<SignTransactionRequest>,
+) -> HandlerResult<SignTransactionData> {
+    let fee_payer = parse_pubkey(&payload.fee_payer, "feePayer")?;
+    let blockhash = parse_blockhash(&payload.recent_blockhash)?;
+    let instructions = compile_specs(&payload.instructions)?;
+    let keypairs = parse_secrets(&payload.secrets)?;
+    let _span = info_span!("sign_transaction", fee_payer = %fee_payer).entered();
+
+    let message = Message::new_with_blockhash(&instructions, Some(&fee_payer), &blockhash);
+    let required = message.signer_keys();
+    let provided: Vec<Pubkey> = keypairs.iter().map(Signer::pubkey).collect();
+    if let Some(stranger) = provided.iter().find(|pubkey| !required.contains(pubkey)) {
+        return Err(AppError::new(
+            ErrorCode::SignerNotInMessage,
+            format!("{} is not a required signer of this transaction", stranger),
+        ));
+    }
+    if let Some(missing) = required.iter().find(|pubkey| !provided.contains(pubkey)) {
+        return Err(AppError::new(
+            ErrorCode::MissingSigner,
+            format!("No secret given for required signer {}", missing),
+        ));
+    }
+
+    let mut transaction = Transaction::new_unsigned(message);
+    transaction
+        .try_sign(&keypairs.iter().collect::<Vec<_>>(), blockhash)
+        .map_err(|e| AppError::new(ErrorCode::InternalError, format!("Signing failed: {}", e)))?;
+    let bytes = serialize_transaction(&transaction, false)?;
+
+    Ok(ApiResponse::from(SignTransactionData {
+        signature: transaction.signatures[0].to_string(),
+        size: bytes.len(),
+        transaction: STANDARD.encode(bytes),
+    }))
+}
+
+/// ------------------ /transaction/send-and-confirm ------------------
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendAndConfirmRequest {
+    /// Base64 of a fully signed, bincode-serialized legacy `Transaction`.
+    pub transaction: String,
+    /// How long to wait for confirmation, in milliseconds, instead of
+    /// `CONFIRM_TIMEOUT_SECS`; clamped to `MAX_RPC_TIMEOUT_MS`. Running out
+    /// still answers with `submitted` rather than a 504.
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendAndConfirmData {
+    pub signature: String,
+    pub confirmed: bool,
+    /// The commitment reached (the RPC client's, normally `finalized`), or
+    /// `submitted` when `CONFIRM_TIMEOUT_SECS` ran out first. A submitted
+    /// transaction may still land; poll its signature to find out.
+    pub status: String,
+}
+
+/// Submits a signed transaction and waits for it to confirm. The signature is
+/// taken from the transaction up front, so a timeout still returns it.
+///
+/// With an `Idempotency-Key` header, a retry of the same transaction gets the
+/// first result back instead of resubmitting (see `IdempotencyCache`).
+pub async fn send_and_confirm(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<SendAndConfirmRequest>,
+) -> HandlerResult<SendAndConfirmData> {
+    let transaction = decode_transaction(&payload.transaction)?;
+    ensure_signature_slots(&transaction)?;
+    // `verify` passes vacuously on an empty signature list, so a fully
+    // signed transaction is also required to have signatures at all.
+    if !transaction.is_signed() || transaction.verify().is_err() {
+        return Err(AppError::new(
+            ErrorCode::InvalidTransaction,
+            "Transaction is not fully signed or a signature does not verify",
+        ));
+    }
+    let signature = *transaction.signatures.first().ok_or_else(|| {
+        AppError::new(ErrorCode::InvalidTransaction, "Transaction has no signatures")
+    })?;
+
+    let claim = match idempotency_key(&headers)? {
+        Some(key) => match state.submissions.claim(key, &signature.to_string())? {
+            Claim::Replay(data) => return Ok(ApiResponse::from(data)),
+            Claim::Fresh(guard) => Some(guard),
+        },
+        None => None,
+    };
+
+    let rpc = state.rpc()?;
+    let timeout = requested_timeout(payload.timeout_ms)?.unwrap_or(config::get().confirm_timeout);
+    let send = rpc.send_and_confirm_transaction(&transaction);
+    let confirmed = match tokio::time::timeout(timeout, send).await {
+        Ok(result) => {
+            result.map_err(rpc_error)?;
+            true
+        }
+        Err(_) => false,
+    };
+
+    let data = SendAndConfirmData {
+        signature: signature.to_string(),
+        confirmed,
+        status: if confirmed {
+            rpc.commitment().commitment.to_string()
+        } else {
+            "submitted".to_string()
+        },
+    };
+    if let Some(claim) = claim {
+        claim.complete(data.clone(), config::get().idempotency_ttl);
+    }
+    Ok(ApiResponse::from(data))
+}
+
+/// Keypairs for a request's `secrets`, each error naming the entry at fault.
+/// At most `MAX_BATCH_SIZE` of them.
+fn parse_secrets(secrets: &[String]) -> Result<Vec<Keypair>, AppError> {
+    ensure_batch_size(secrets.len())?;
+    secrets
+        .iter()
+        .enumerate()
+        .map(|(i, secret)| {
+            decode_secret(secret)
+                .and_then(|bytes| signing_keypair(&bytes))
+                .map_err(|e| AppError::new(e.code, format!("secrets[{}]: {}", i, e.message)))
+        })
+        .collect()
+}
+
+fn signing_keypair(secret: &[u8]) -> Result<Keypair, AppError> {
+    Keypair::from_bytes(secret).map_err(|_| {
+        AppError::new(ErrorCode::InvalidSecret, "Failed to parse secret key into Keypair")
+    })
+}
+
+/// ------------------ /keypair/sign-and-send ------------------
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignAndSendRequest {
+    /// Base64 of a bincode-serialized legacy `Transaction`; existing
+    /// signatures are kept.
+    pub transaction: String,
+    /// Base58 secret keys, one per required signer still missing.
+    pub secrets: Vec<String>,
+    /// Deadline for each RPC call, in milliseconds; clamped to
+    /// `MAX_RPC_TIMEOUT_MS`.
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignAndSendData {
+    pub signature: String,
+}
+
+/// Signs a transaction with the given secrets and submits it, for automation
+/// that already trusts this service with keys. Off unless
+/// `ALLOW_KEY_SUBMISSION=true`.
+pub async fn sign_and_send(
+    State(state): State<AppState>,
+    Json(payload): Json<SignAndSendRequest>,
+) -> HandlerResult<SignAndSendData> {
+    if !config::get().allow_key_submission {
+        return Err(AppError::new(
+            ErrorCode::EndpointDisabled,
+            "Signing with submitted keys is disabled: set ALLOW_KEY_SUBMISSION=true to enable it",
+        ));
+    }
+
+    let mut transaction = decode_transaction(&payload.transaction)?;
+    ensure_signature_slots(&transaction)?;
+    let keypairs = parse_secrets(&payload.secrets)?;
+
+    let required = transaction.message.signer_keys();
+    if let Some(stranger) = keypairs
+        .iter()
+        .map(Signer::pubkey)
+        .find(|pubkey| !required.contains(&pubkey))
+    {
+        return Err(AppError::new(
+            ErrorCode::SignerNotInMessage,
+            format!("{} is not a required signer of this transaction", stranger),
+        ));
+    }
+
+    let blockhash = transaction.message.recent_blockhash;
+    transaction
+        .try_partial_sign(&keypairs.iter().collect::<Vec<_>>(), blockhash)
+        .map_err(|e| AppError::new(ErrorCode::InternalError, format!("Signing failed: {}", e)))?;
+    if !transaction.is_signed() {
+        return Err(AppError::new(
+            ErrorCode::InvalidTransaction,
+            "Transaction still has unsigned required signers after signing",
+        ));
+    }
+
+    let rpc = state.rpc()?;
+    let signature = within(payload.timeout_ms, rpc.send_transaction(&transaction))
+        .await?
+        .map_err(rpc_error)?;
+
+    Ok(ApiResponse::from(SignAndSendData {
+        signature: signature.to_string(),
+    }))
+}
+
+/// ------------------ /transaction/deserialize-and-resign ------------------
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResignRequest {
+    /// Base64 of a bincode-serialized legacy `Transaction`, already signed by
+    /// the other signers.
+    pub transaction: String,
+    /// Base58 secret key of the fee payer, the message's first account; give
+    /// either this or `keyAlias`.
+    #[serde(default)]
+    pub secret: Option<String>,
+    /// Name of a server-held key (`SERVER_KEYS`) to sign with instead.
+    #[serde(default)]
+    pub key_alias: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResignData {
+    /// Base64 of the transaction with the fee payer's signature added.
+    pub transaction: String,
+    /// The fee payer's signature, which is also the transaction id.
+    pub signature: String,
+    /// Whether every required signer has now signed.
+    pub complete: bool,
+}
+
+/// Adds the fee payer's signature to a transaction the other signers have
+/// already signed, for relayers that sponsor fees. Signatures already present
+/// must verify against the message as received, so a transaction altered
+/// after signing is rejected instead of being returned with stale signatures.
+pub async fn deserialize_and_resign(
+    State(_): State<AppState>,
+    Json(payload): Json<ResignRequest>,
+) -> HandlerResult<ResignData> {
+    let mut transaction = decode_transaction(&payload.transaction)?;
+    let secret_bytes = signing_secret(payload.secret.as_deref(), payload.key_alias.as_deref())?;
+    let fee_payer = signing_keypair(&secret_bytes)?;
+    let _span = info_span!("deserialize_and_resign", fee_payer = %fee_payer.pubkey()).entered();
+
+    if transaction.message.account_keys.first() != Some(&fee_payer.pubkey()) {
+        return Err(AppError::new(
+            ErrorCode::SignerNotInMessage,
+            format!("{} is not the fee payer of this transaction", fee_payer.pubkey()),
+        ));
+    }
+    ensure_signature_slots(&transaction)?;
+
+    let message_bytes = transaction.message_data();
+    let signers = transaction.message.signer_keys();
+    // The fee payer's slot is skipped: it is about to be overwritten.
+    for (signature, pubkey) in transaction.signatures.iter().zip(&signers).skip(1) {
+        if *signature != Signature::default() && !signature.verify(pubkey.as_ref(), &message_bytes)
+        {
+            return Err(AppError::new(
+                ErrorCode::InvalidSignature,
+                format!(
+                    "Signature of {} doesn't match the message; was the transaction changed \
+                     after it was signed?",
+                    pubkey
+                ),
+            ));
+        }
+    }
+
+    // Signing with the message's own blockhash leaves the other signatures in
+    // place.
+    let blockhash = transaction.message.recent_blockhash;
+    transaction
+        .try_partial_sign(&[&fee_payer], blockhash)
+        .map_err(|e| AppError::new(ErrorCode::InternalError, format!("Signing failed: {}", e)))?;
+    let bytes = bincode::serialize(&transaction).map_err(|e| {
+        AppError::new(ErrorCode::InternalError, format!("Failed to serialize transaction: {}", e))
+    })?;
+
+    Ok(ApiResponse::from(ResignData {
+        transaction: STANDARD.encode(bytes),
+        signature: transaction.signatures[0].to_string(),
+        complete: transaction.is_signed(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sign_request(fee_payer: &Keypair, secrets: &[&Keypair]) -> SignTransactionRequest {
+        serde_json::from_value(json!({
+            "feePayer": fee_payer.pubkey().to_string(),
+            "recentBlockhash": Hash::new_unique().to_string(),
+            "instructions": [{
+                "type": "sendSol",
+                "from": fee_payer.pubkey().to_string(),
+                "to": Pubkey::new_unique().to_string(),
+                "lamports": 1_000,
+            }],
+            "secrets": secrets
+                .iter()
+                .map(|keypair| keypair.to_base58_string())
+                .collect::<Vec<_>>(),
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn sign_transaction_signs_sol_transfer_as_fee_payer() {
+        let fee_payer = Keypair::new();
+        let data = sign_transaction(
+            State(AppState::for_tests()),
+            Json(sign_request(&fee_payer, &[&fee_payer])),
+        )
+        .await
+        .unwrap()
+        .into_data();
+
+        let bytes = STANDARD.decode(&data.transaction).unwrap();
+        assert_eq!(data.size, bytes.len());
+        let transaction: Transaction = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(transaction.message.account_keys[0], fee_payer.pubkey());
+        assert_eq!(transaction.signatures.len(), 1);
+        assert!(transaction.signatures[0]
+            .verify(fee_payer.pubkey().as_ref(), &transaction.message_data()));
+        assert_eq!(data.signature, transaction.signatures[0].to_string());
+    }
+
+    #[tokio::test]
+    async fn sign_transaction_names_missing_signer() {
+        let fee_payer = Keypair::new();
+        let request = sign_request(&fee_payer, &[]);
+        let err = sign_transaction(State(AppState::for_tests()), Json(request))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, ErrorCode::MissingSigner);
+        assert!(err.message.contains(&fee_payer.pubkey().to_string()));
+    }
+
+    #[tokio::test]
+    async fn inspect_transaction_rejects_oversized_input() {
+        let state = AppState::for_tests();
+        let max = config::get().max_transaction_bytes;
+        // Just over the cap, caught after decoding; and far over it (yet well
+        // under the body limit), screened out before decoding.
+        for len in [max + 1, 64 * 1024] {
+            let request: InspectTransactionRequest =
+                serde_json::from_value(json!({ "transaction": STANDARD.encode(vec![0; len]) }))
+                    .unwrap();
+            let err = inspect_transaction(State(state.clone()), Json(request))
+                .await
+                .unwrap_err();
+            assert_eq!(err.code, ErrorCode::TransactionTooLarge, "{len} bytes");
+            assert_eq!(err.code.status(), axum::http::StatusCode::PAYLOAD_TOO_LARGE);
+        }
+    }
+
+    #[test]
+    fn parse_secrets_names_the_bad_entry() {
+        config::init_for_tests();
+        let secrets = [Keypair::new().to_base58_string(), "not-a-secret".to_string()];
+        let err = parse_secrets(&secrets).unwrap_err();
+        assert_eq!(err.code, ErrorCode::InvalidSecret);
+        assert!(err.message.starts_with("secrets[1]: "), "{}", err.message);
+    }
+
+    #[test]
+    fn parse_secrets_caps_the_count() {
+        config::init_for_tests();
+        let max = config::get().max_batch_size;
+        let secrets = vec![Keypair::new().to_base58_string(); max + 1];
+        assert_eq!(parse_secrets(&secrets).unwrap_err().code, ErrorCode::BatchTooLarge);
+    }
+}
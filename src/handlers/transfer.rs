@@ -1,12 +1,30 @@
-use std::str::FromStr;
-
-use axum::Json;
-use base64::engine::general_purpose::STANDARD;
-use base64::Engine;
+use axum::{
+    extract::{Query, State},
+    Json,
+};
 use serde::{Deserialize, Serialize};
-use solana_program::pubkey::Pubkey;
-use solana_sdk::system_instruction;
-use spl_token::instruction::transfer_checked;
+use solana_program::{instruction::Instruction, program_error::ProgramError, pubkey::Pubkey};
+use solana_sdk::{system_instruction, system_program};
+use spl_associated_token_account::{
+    get_associated_token_address_with_program_id,
+    instruction::create_associated_token_account_idempotent,
+};
+use spl_token::native_mint;
+use spl_token_2022::instruction::transfer_checked;
+use tracing::{field, info_span};
+
+use super::amount::{raw_amount_to_ui, ui_amount_to_raw};
+use super::token::default_checked;
+use crate::config;
+use crate::error::{AppError, ErrorCode, FieldErrors};
+use crate::proto::{Negotiated, ResponseFormat, ToProto};
+use crate::response::{ApiResponse, HandlerResult};
+use crate::state::AppState;
+use crate::types::{
+    instruction_to_response, parse_mint, parse_pubkey, resolve_token_program, with_compiled,
+    AccountMeta, BuildOutcome, CompiledQuery, DryRunQuery, Encoding, InstructionSequence,
+    InstructionType, TokenInstructionResponse, TokenProgram, WithCompiled,
+};
 
 //
 // REQUEST TYPES
@@ -17,193 +35,572 @@ use spl_token::instruction::transfer_checked;
 pub struct SendSolRequest {
     pub from: String,
     pub to: String,
-    pub lamports: u64,
+    /// Exactly one of `lamports` and `sol` must be given.
+    #[serde(default)]
+    pub lamports: Option<u64>,
+    /// Decimal SOL string such as `"1.5"`, converted to lamports.
+    #[serde(default)]
+    pub sol: Option<String>,
+    /// Account paying the transaction fee, added to the returned `accounts`.
+    /// Defaults to `from` when omitted, which adds nothing.
+    #[serde(default)]
+    pub fee_payer: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SendTokenRequest {
     pub destination: String,
-    pub mint: String,
+    /// Required unless `checked` is false and `createDestinationAta` isn't
+    /// set.
+    #[serde(default)]
+    pub mint: Option<String>,
     pub owner: String,
     pub amount: u64,
+    /// `transfer_checked` (the default), which also pins the mint and
+    /// decimals. `false` builds plain `transfer` for legacy flows.
+    #[serde(default = "default_checked")]
+    pub checked: bool,
+    /// `token` or `token-2022`; defaults to the server's `DEFAULT_TOKEN_PROGRAM`.
+    #[serde(default)]
+    pub token_program: Option<TokenProgram>,
+    /// Treat `destination` as a wallet: send to its associated token account
+    /// and prepend an idempotent create of that account, so the transfer works
+    /// whether or not it exists yet.
+    #[serde(default)]
+    pub create_destination_ata: bool,
+    /// Pays for the destination ATA when `createDestinationAta` is set.
+    /// Defaults to `owner`.
+    #[serde(default)]
+    pub funder: Option<String>,
 }
 
 //
 // RESPONSE STRUCTS
 //
 
+/// The transfer alone, or `[create_associated_token_account_idempotent,
+/// transfer]` when `createDestinationAta` is set.
 #[derive(Debug, Serialize)]
-pub struct ApiSuccessResponse<T> {
-    pub success: bool,
-    pub data: T,
+#[serde(untagged)]
+pub enum SendTokenResponse {
+    Instruction(TokenInstructionResponse),
+    Sequence(InstructionSequence),
 }
 
-#[derive(Debug, Serialize)]
-pub struct ApiErrorResponse {
-    pub success: bool,
-    pub error: String,
+impl ToProto for SendTokenResponse {
+    fn encode_proto(&self) -> Option<Vec<u8>> {
+        match self {
+            SendTokenResponse::Instruction(ix) => ix.encode_proto(),
+            SendTokenResponse::Sequence(sequence) => sequence.encode_proto(),
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
-pub struct SolInstructionResponse {
-    pub program_id: String,
-    pub accounts: Vec<String>,
-    #[serde(rename = "instruction_data")]
-    pub instruction_data: String,
+pub struct SendTokenData {
+    #[serde(flatten)]
+    pub instructions: SendTokenResponse,
+    /// `transfer_checked` or `transfer`.
+    pub variant: &'static str,
 }
 
-#[derive(Debug, Serialize)]
-pub struct CompactAccountMeta {
-    pub pubkey: String,
-    #[serde(rename = "isSigner")]
-    pub is_signer: bool,
+impl ToProto for SendTokenData {
+    fn encode_proto(&self) -> Option<Vec<u8>> {
+        self.instructions.encode_proto()
+    }
 }
 
 #[derive(Debug, Serialize)]
-pub struct TokenInstructionResponse {
-    pub program_id: String,
-    pub accounts: Vec<CompactAccountMeta>,
-    #[serde(rename = "instruction_data")]
-    pub instruction_data: String,
+pub struct SolInstructionResponse {
+    #[serde(flatten)]
+    pub instruction: TokenInstructionResponse,
+    pub lamports: u64,
+    /// `lamports` as a decimal SOL string, kept as text to avoid float rounding.
+    pub sol: String,
 }
 
 //
 // HANDLER: /send/sol
 //
 
-pub async fn send_sol(Json(payload): Json<SendSolRequest>) -> Json<ApiSuccessResponse<SolInstructionResponse>> {
-    let from = match Pubkey::from_str(&payload.from) {
-        Ok(p) => p,
-        Err(_) => {
-            return Json(ApiSuccessResponse {
-                success: false,
-                data: SolInstructionResponse {
-                    program_id: "".to_string(),
-                    accounts: vec![],
-                    instruction_data: base64::encode("Invalid 'from' pubkey"),
-                },
-            })
-        }
+pub async fn send_sol(
+    State(_): State<AppState>,
+    Query(query): Query<DryRunQuery>,
+    Query(compiled): Query<CompiledQuery>,
+    Json(payload): Json<SendSolRequest>,
+) -> HandlerResult<BuildOutcome<WithCompiled<SolInstructionResponse>>> {
+    let (from, to, _, _) = parse_send_sol(&payload)?;
+    let _span = info_span!(
+        "send_sol",
+        program_id = %system_program::id(),
+        instruction_type = InstructionType::SystemTransfer.as_str(),
+        %from,
+        %to,
+    )
+    .entered();
+
+    if query.dry_run {
+        return Ok(ApiResponse::from(BuildOutcome::validated()));
+    }
+
+    let transfer = sol_transfer(&payload)?;
+    let payer = transfer.fee_payer.unwrap_or(transfer.from);
+
+    Ok(ApiResponse::from(BuildOutcome::Built(with_compiled(
+        transfer.to_response(),
+        &compiled,
+        &[transfer.ix],
+        Some(&payer),
+    ))))
+}
+
+fn parse_send_sol(
+    payload: &SendSolRequest,
+) -> Result<(Pubkey, Pubkey, Option<Pubkey>, u64), AppError> {
+    let mut errors = FieldErrors::default();
+    let from = errors.check("from", parse_pubkey(&payload.from, "from"));
+    let to = errors.check("to", parse_pubkey(&payload.to, "to"));
+    let fee_payer = errors.check(
+        "feePayer",
+        payload
+            .fee_payer
+            .as_deref()
+            .map(|p| parse_pubkey(p, "feePayer"))
+            .transpose(),
+    );
+    let lamports = match (payload.lamports, payload.sol.as_deref()) {
+        (Some(lamports), None) => errors.check("lamports", nonzero(lamports, "lamports")),
+        (None, Some(sol)) => errors.check(
+            "sol",
+            ui_amount_to_raw(sol, native_mint::DECIMALS)
+                .and_then(|lamports| nonzero(lamports, "sol")),
+        ),
+        _ => errors.check::<u64>(
+            "lamports",
+            Err(AppError::new(
+                ErrorCode::InvalidAmount,
+                "Provide exactly one of lamports or sol",
+            )),
+        ),
     };
 
-    let to = match Pubkey::from_str(&payload.to) {
-        Ok(p) => p,
-        Err(_) => {
-            return Json(ApiSuccessResponse {
-                success: false,
-                data: SolInstructionResponse {
-                    program_id: "".to_string(),
-                    accounts: vec![],
-                    instruction_data: base64::encode("Invalid 'to' pubkey"),
-                },
-            })
+    match (from, to, fee_payer, lamports) {
+        (Some(from), Some(to), Some(fee_payer), Some(lamports)) => {
+            Ok((from, to, fee_payer, lamports))
         }
-    };
+        _ => Err(errors.into_error()),
+    }
+}
 
-    let ix = system_instruction::transfer(&from, &to, payload.lamports);
+pub fn build_send_sol(payload: &SendSolRequest) -> Result<SolInstructionResponse, AppError> {
+    Ok(sol_transfer(payload)?.to_response())
+}
+
+/// Just the transfer instruction, for composing into a transaction; any
+/// `feePayer` is left to the transaction.
+pub fn send_sol_instruction(payload: &SendSolRequest) -> Result<Instruction, AppError> {
+    Ok(sol_transfer(payload)?.ix)
+}
 
-    let accounts: Vec<String> = ix.accounts.iter().map(|meta| meta.pubkey.to_string()).collect();
+/// A built SOL transfer and the request details its response echoes.
+struct SolTransfer {
+    ix: Instruction,
+    from: Pubkey,
+    fee_payer: Option<Pubkey>,
+    lamports: u64,
+}
+
+fn sol_transfer(payload: &SendSolRequest) -> Result<SolTransfer, AppError> {
+    let (from, to, fee_payer, lamports) = parse_send_sol(payload)?;
 
-    Json(ApiSuccessResponse {
-        success: true,
-        data: SolInstructionResponse {
-            program_id: ix.program_id.to_string(),
-            accounts,
-            instruction_data: base64::encode(ix.data),
-        },
+    Ok(SolTransfer {
+        ix: system_instruction::transfer(&from, &to, lamports),
+        from,
+        fee_payer,
+        lamports,
     })
 }
 
+impl SolTransfer {
+    /// A separate fee payer is appended to `accounts` as signer and writable,
+    /// after the transfer's own `from` and `to`, whose positions the system
+    /// program relies on.
+    fn to_response(&self) -> SolInstructionResponse {
+        let mut instruction =
+            instruction_to_response(&self.ix, InstructionType::SystemTransfer, Encoding::Base64);
+        if let Some(fee_payer) = self.fee_payer.filter(|fee_payer| *fee_payer != self.from) {
+            instruction.accounts.push(AccountMeta {
+                pubkey: fee_payer.to_string(),
+                is_signer: true,
+                is_writable: true,
+            });
+        }
+        SolInstructionResponse {
+            instruction,
+            lamports: self.lamports,
+            sol: raw_amount_to_ui(self.lamports, native_mint::DECIMALS),
+        }
+    }
+}
+
 //
 // HANDLER: /send/token
 //
 
-pub async fn send_token(Json(payload): Json<SendTokenRequest>) -> Json<ApiSuccessResponse<TokenInstructionResponse>> {
-    let destination = match Pubkey::from_str(&payload.destination) {
-        Ok(p) => p,
-        Err(_) => return Json(ApiSuccessResponse {
-            success: false,
-            data: TokenInstructionResponse {
-                program_id: "".to_string(),
-                accounts: vec![],
-                instruction_data: base64::encode("Invalid destination pubkey"),
-            },
-        }),
-    };
+pub async fn send_token(
+    State(_): State<AppState>,
+    format: ResponseFormat,
+    Query(query): Query<DryRunQuery>,
+    Query(compiled): Query<CompiledQuery>,
+    Json(payload): Json<SendTokenRequest>,
+) -> Result<Negotiated<BuildOutcome<WithCompiled<SendTokenData>>>, AppError> {
+    let params = parse_send_token(&payload)?;
+    let _span = info_span!(
+        "send_token",
+        program_id = %resolve_token_program(payload.token_program),
+        instruction_type = transfer_type(payload.checked).as_str(),
+        mint = params.mint.as_ref().map(field::display),
+        owner = %params.owner,
+        destination = %params.destination,
+    )
+    .entered();
 
-    let mint = match Pubkey::from_str(&payload.mint) {
-        Ok(p) => p,
-        Err(_) => return Json(ApiSuccessResponse {
-            success: false,
-            data: TokenInstructionResponse {
-                program_id: "".to_string(),
-                accounts: vec![],
-                instruction_data: base64::encode("Invalid mint pubkey"),
-            },
-        }),
+    if query.dry_run {
+        return Ok(format.negotiate(BuildOutcome::validated()));
+    }
+
+    let steps = send_token_steps(&payload)?;
+    let response = send_token_response(&payload, &steps);
+    let instructions: Vec<Instruction> = steps.into_iter().map(|(_, ix)| ix).collect();
+    let response = with_compiled(response, &compiled, &instructions, None);
+    Ok(format.negotiate(BuildOutcome::Built(response)))
+}
+
+struct SendTokenParams {
+    destination: Pubkey,
+    /// `None` only for a plain `transfer` that was given no mint.
+    mint: Option<Pubkey>,
+    owner: Pubkey,
+    /// Set when `createDestinationAta` asks for the ATA to be created.
+    funder: Option<Pubkey>,
+}
+
+fn parse_send_token(payload: &SendTokenRequest) -> Result<SendTokenParams, AppError> {
+    let mut errors = FieldErrors::default();
+    let mint = match payload.mint.as_deref() {
+        // A plain transfer doesn't name the mint, so the whitelist couldn't
+        // hold for it.
+        _ if !payload.checked && config::get().mint_whitelist.is_some() => Err(AppError::new(
+            ErrorCode::MintNotAllowed,
+            "checked: false is unavailable while MINT_WHITELIST is set",
+        )),
+        Some(mint) => parse_mint(mint).map(Some),
+        None if payload.checked || payload.create_destination_ata => Err(AppError::new(
+            ErrorCode::InvalidPubkey,
+            "mint is required unless checked is false and createDestinationAta is unset",
+        )),
+        None => Ok(None),
     };
+    let mint_key = mint.as_ref().ok().copied().flatten();
+    let destination = errors.check(
+        "destination",
+        parse_pubkey(&payload.destination, "destination")
+            .and_then(|key| reject_mint(key, mint_key, "destination")),
+    );
+    let mint = errors.check("mint", mint);
+    let owner = errors.check(
+        "owner",
+        parse_pubkey(&payload.owner, "owner").and_then(|key| reject_mint(key, mint_key, "owner")),
+    );
+    // `None` when omitted, `Some(None)` when given but rejected.
+    let funder = payload.funder.as_deref().map(|funder| {
+        let funder = match payload.create_destination_ata {
+            true => parse_pubkey(funder, "funder"),
+            false => Err(AppError::new(
+                ErrorCode::InvalidPubkey,
+                "funder is only used with createDestinationAta",
+            )),
+        };
+        errors.check("funder", funder)
+    });
+    let amount = errors.check("amount", nonzero(payload.amount, "amount"));
 
-    let owner = match Pubkey::from_str(&payload.owner) {
-        Ok(p) => p,
-        Err(_) => return Json(ApiSuccessResponse {
-            success: false,
-            data: TokenInstructionResponse {
-                program_id: "".to_string(),
-                accounts: vec![],
-                instruction_data: base64::encode("Invalid owner pubkey"),
-            },
-        }),
+    match (destination, mint, owner, funder, amount) {
+        (Some(destination), Some(mint), Some(owner), None | Some(Some(_)), Some(_)) => {
+            Ok(SendTokenParams {
+                destination,
+                mint,
+                owner,
+                funder: payload
+                    .create_destination_ata
+                    .then(|| funder.flatten().unwrap_or(owner)),
+            })
+        }
+        _ => Err(errors.into_error()),
+    }
+}
+
+/// A transfer of nothing is always a client mistake.
+fn nonzero(amount: u64, field: &str) -> Result<u64, AppError> {
+    match amount {
+        0 => Err(AppError::new(
+            ErrorCode::AmountZero,
+            format!("{} must be greater than 0", field),
+        )),
+        amount => Ok(amount),
+    }
+}
+
+/// The mint pasted in place of a wallet is a frequent copy-paste bug, and no
+/// transfer can succeed with it, so it is caught here rather than on-chain.
+fn reject_mint(key: Pubkey, mint: Option<Pubkey>, field: &str) -> Result<Pubkey, AppError> {
+    if Some(key) == mint {
+        return Err(AppError::new(
+            ErrorCode::InvalidPubkey,
+            format!("{} is the mint address; check that the fields aren't swapped", field),
+        ));
+    }
+    Ok(key)
+}
+
+/// `transfer` without the mint and decimals checks. Token-2022 deprecates it in
+/// favour of `transfer_checked`, which is why it is opt-in; both programs
+/// still process it for mints without extensions that require the checked
+/// form.
+#[allow(deprecated)]
+fn plain_transfer(
+    token_program: &Pubkey,
+    owner: &Pubkey,
+    destination: &Pubkey,
+    amount: u64,
+) -> Result<Instruction, ProgramError> {
+    spl_token_2022::instruction::transfer(token_program, owner, destination, owner, &[], amount)
+}
+
+fn transfer_type(checked: bool) -> InstructionType {
+    match checked {
+        true => InstructionType::SplTransferChecked,
+        false => InstructionType::SplTransfer,
+    }
+}
+
+fn send_token_response(
+    payload: &SendTokenRequest,
+    steps: &[(InstructionType, Instruction)],
+) -> SendTokenData {
+    let instructions = match steps {
+        [(instruction_type, ix)] => SendTokenResponse::Instruction(instruction_to_response(
+            ix,
+            *instruction_type,
+            Encoding::Base64,
+        )),
+        sequence => SendTokenResponse::Sequence(InstructionSequence::new(
+            sequence.iter().map(|(instruction_type, ix)| (*instruction_type, ix)),
+        )),
     };
+    SendTokenData {
+        instructions,
+        variant: if payload.checked { "transfer_checked" } else { "transfer" },
+    }
+}
 
-    let decimals: u8 = 6; // Adjust if your mint uses a different value
-
-    let ix = match transfer_checked(
-        &spl_token::id(),
-        &owner,        // source
-        &mint,
-        &destination,  // destination
-        &owner,        // authority
-        &[],           // signers
-        payload.amount,
-        decimals,
-    ) {
-        Ok(ix) => ix,
-        Err(e) => return Json(ApiSuccessResponse {
-            success: false,
-            data: TokenInstructionResponse {
-                program_id: "".to_string(),
-                accounts: vec![],
-                instruction_data: STANDARD.encode(e.to_string()),
-            },
-        }),
+pub fn build_send_token(payload: &SendTokenRequest) -> Result<SendTokenData, AppError> {
+    Ok(send_token_response(payload, &send_token_steps(payload)?))
+}
+
+/// The bare instructions, in order, for composing into a transaction.
+pub fn send_token_instructions(payload: &SendTokenRequest) -> Result<Vec<Instruction>, AppError> {
+    Ok(send_token_steps(payload)?.into_iter().map(|(_, ix)| ix).collect())
+}
+
+fn send_token_steps(
+    payload: &SendTokenRequest,
+) -> Result<Vec<(InstructionType, Instruction)>, AppError> {
+    let params = parse_send_token(payload)?;
+    let token_program = resolve_token_program(payload.token_program);
+
+    let decimals = config::get().default_token_decimals;
+
+    let mut steps = Vec::new();
+    // `parse_send_token` requires the mint whenever the ATA is created.
+    let destination = match (params.funder, params.mint) {
+        (Some(funder), Some(mint)) => {
+            steps.push((
+                InstructionType::CreateAssociatedTokenAccountIdempotent,
+                create_associated_token_account_idempotent(
+                    &funder,
+                    &params.destination,
+                    &mint,
+                    &token_program,
+                ),
+            ));
+            get_associated_token_address_with_program_id(&params.destination, &mint, &token_program)
+        }
+        _ => params.destination,
     };
 
-    let accounts: Vec<CompactAccountMeta> = ix.accounts.iter().map(|meta| CompactAccountMeta {
-        pubkey: meta.pubkey.to_string(),
-        is_signer: meta.is_signer,
-    }).collect();
-
-    Json(ApiSuccessResponse {
-        success: true,
-        data: TokenInstructionResponse {
-            program_id: ix.program_id.to_string(),
-            accounts,
-            instruction_data: base64::encode(ix.data),
-        },
-    })
+    let ix = if payload.checked {
+        let mint = params.mint.ok_or_else(|| {
+            AppError::new(ErrorCode::InvalidPubkey, "mint is required when checked is true")
+        })?;
+        transfer_checked(
+            &token_program,
+            &params.owner, // source
+            &mint,
+            &destination,  // destination
+            &params.owner, // authority
+            &[],           // signers
+            payload.amount,
+            decimals,
+        )
+    } else {
+        plain_transfer(&token_program, &params.owner, &destination, payload.amount)
+    }
+    .map_err(|e| AppError::new(ErrorCode::InstructionBuildFailed, e.to_string()))?;
+    steps.push((transfer_type(payload.checked), ix));
+
+    Ok(steps)
 }
 
-//
-// HELPER
-//
+#[cfg(test)]
+mod tests {
+    use spl_token_2022::instruction::TokenInstruction;
+
+    use super::*;
+
+    fn send_token_request(checked: Option<bool>) -> SendTokenRequest {
+        let mut request = serde_json::json!({
+            "destination": Pubkey::new_unique().to_string(),
+            "mint": Pubkey::new_unique().to_string(),
+            "owner": Pubkey::new_unique().to_string(),
+            "amount": 250,
+        });
+        if let Some(checked) = checked {
+            request["checked"] = checked.into();
+        }
+        serde_json::from_value(request).unwrap()
+    }
+
+    #[test]
+    fn send_token_defaults_to_transfer_checked() {
+        config::init_for_tests();
+        let steps = send_token_steps(&send_token_request(None)).unwrap();
+
+        let [(instruction_type, ix)] = steps.as_slice() else {
+            panic!("expected one instruction, got {}", steps.len());
+        };
+        assert_eq!(*instruction_type, InstructionType::SplTransferChecked);
+        assert_eq!(
+            TokenInstruction::unpack(&ix.data).unwrap(),
+            TokenInstruction::TransferChecked {
+                amount: 250,
+                decimals: config::get().default_token_decimals,
+            }
+        );
+    }
+
+    #[test]
+    fn send_token_unchecked_builds_plain_transfer() {
+        config::init_for_tests();
+        let mut request = send_token_request(Some(false));
+        request.mint = None;
+        let steps = send_token_steps(&request).unwrap();
+
+        let [(instruction_type, ix)] = steps.as_slice() else {
+            panic!("expected one instruction, got {}", steps.len());
+        };
+        assert_eq!(*instruction_type, InstructionType::SplTransfer);
+        #[allow(deprecated)]
+        let expected = TokenInstruction::Transfer { amount: 250 };
+        assert_eq!(TokenInstruction::unpack(&ix.data).unwrap(), expected);
+        assert_eq!(send_token_response(&request, &steps).variant, "transfer");
+    }
+
+    fn send_sol_errors(amount: serde_json::Value) -> Vec<(String, String, ErrorCode)> {
+        let mut request = serde_json::json!({
+            "from": "not-a-pubkey",
+            "to": Pubkey::new_unique().to_string(),
+        });
+        request.as_object_mut().unwrap().extend(amount.as_object().unwrap().clone());
+        let request: SendSolRequest = serde_json::from_value(request).unwrap();
+        parse_send_sol(&request)
+            .unwrap_err()
+            .fields
+            .into_iter()
+            .map(|field| (field.field, field.error, field.code))
+            .collect()
+    }
+
+    #[test]
+    fn send_sol_lists_zero_amount_with_other_fields() {
+        for (amount, field) in [
+            (serde_json::json!({ "lamports": 0 }), "lamports"),
+            (serde_json::json!({ "sol": "0" }), "sol"),
+        ] {
+            let errors = send_sol_errors(amount);
+            assert_eq!(errors.len(), 2);
+            assert_eq!(errors[0].0, "from");
+            assert_eq!(errors[1].0, field);
+            assert_eq!(errors[1].1, format!("{} must be greater than 0", field));
+            assert_eq!(errors[1].2, ErrorCode::AmountZero);
+        }
+    }
+
+    #[test]
+    fn send_token_rejects_zero_amount() {
+        config::init_for_tests();
+        let mut request = send_token_request(None);
+        request.amount = 0;
+        let err = send_token_steps(&request).unwrap_err();
+        assert_eq!(err.code, ErrorCode::AmountZero);
+        assert_eq!(err.fields[0].field, "amount");
+    }
+
+    #[test]
+    fn send_token_creating_destination_ata_sequence() {
+        config::init_for_tests();
+        let mut request = send_token_request(None);
+        request.create_destination_ata = true;
+        request.token_program = Some(TokenProgram::Token2022);
+        let data =
+            serde_json::to_value(build_send_token(&request).unwrap().instructions).unwrap();
+
+        assert_eq!(
+            data["steps"],
+            serde_json::json!(["createAssociatedTokenAccountIdempotent", "splTransferChecked"])
+        );
+        let program_ids: Vec<&str> = data["instructions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|ix| ix["program_id"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            program_ids,
+            [
+                spl_associated_token_account::id().to_string(),
+                spl_token_2022::id().to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn send_sol_lists_fee_payer_in_accounts() {
+        let fee_payer = Pubkey::new_unique();
+        let request: SendSolRequest = serde_json::from_value(serde_json::json!({
+            "from": Pubkey::new_unique().to_string(),
+            "to": Pubkey::new_unique().to_string(),
+            "lamports": 1_000,
+            "feePayer": fee_payer.to_string(),
+        }))
+        .unwrap();
 
-fn error_response(msg: &str) -> ApiSuccessResponse<ApiErrorResponse> {
-    ApiSuccessResponse {
-        success: false,
-        data: ApiErrorResponse {
-            success: false,
-            error: msg.to_string(),
-        },
+        let accounts = build_send_sol(&request).unwrap().instruction.accounts;
+        assert_eq!(accounts.len(), 3);
+        assert_eq!(accounts[0].pubkey, request.from);
+        assert_eq!(accounts[1].pubkey, request.to);
+        assert_eq!(accounts[2].pubkey, fee_payer.to_string());
+        assert!(accounts[2].is_signer && accounts[2].is_writable);
     }
 }
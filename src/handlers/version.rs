@@ -0,0 +1,26 @@
+use axum::extract::State;
+use serde::Serialize;
+
+use crate::response::ApiResponse;
+use crate::state::AppState;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionInfo {
+    pub name: &'static str,
+    pub version: &'static str,
+    pub solana_sdk: &'static str,
+    pub git_commit: &'static str,
+    pub build_timestamp: u64,
+}
+
+/// Reports the crate and solana-sdk versions this binary was built with.
+pub async fn version(State(_): State<AppState>) -> ApiResponse<VersionInfo> {
+    ApiResponse::from(VersionInfo {
+        name: env!("CARGO_PKG_NAME"),
+        version: env!("CARGO_PKG_VERSION"),
+        solana_sdk: env!("SOLANA_SDK_VERSION"),
+        git_commit: env!("GIT_COMMIT"),
+        build_timestamp: env!("BUILD_TIMESTAMP").parse().unwrap_or(0),
+    })
+}
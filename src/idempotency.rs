@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use axum::http::HeaderMap;
+
+use crate::error::{AppError, ErrorCode};
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+const MAX_KEY_LEN: usize = 255;
+
+/// Results of RPC-submitting requests, keyed by the client's
+/// `Idempotency-Key`, so a retried request gets the first result back instead
+/// of submitting again. Entries live for `IDEMPOTENCY_TTL_SECS`.
+///
+/// The cache is per process: instances behind a load balancer don't share it,
+/// and a restart empties it.
+pub struct IdempotencyCache<T> {
+    entries: Arc<Mutex<HashMap<String, Entry<T>>>>,
+}
+
+// Derived `Clone` would needlessly require `T: Clone`.
+impl<T> Clone for IdempotencyCache<T> {
+    fn clone(&self) -> Self {
+        Self {
+            entries: self.entries.clone(),
+        }
+    }
+}
+
+struct Entry<T> {
+    /// Identifies the request body, so reusing a key for a different request
+    /// is caught rather than answered with an unrelated result.
+    fingerprint: String,
+    /// `None` while the first request is still running.
+    result: Option<(T, Instant)>,
+}
+
+/// What to do with a request that carried an `Idempotency-Key`.
+pub enum Claim<T> {
+    /// First use of the key: run the request and `complete` the guard.
+    Fresh(ClaimGuard<T>),
+    /// The key already has a result; return it without running again.
+    Replay(T),
+}
+
+impl<T: Clone> IdempotencyCache<T> {
+    pub fn new() -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Claims `key` for a request identified by `fingerprint`. A key that is
+    /// still in flight, or was used for a different request, is a 409.
+    pub fn claim(&self, key: &str, fingerprint: &str) -> Result<Claim<T>, AppError> {
+        let mut entries = self.lock();
+        let now = Instant::now();
+        entries.retain(|_, entry| {
+            entry
+                .result
+                .as_ref()
+                .is_none_or(|(_, expires)| *expires > now)
+        });
+
+        if let Some(entry) = entries.get(key) {
+            if entry.fingerprint != fingerprint {
+                return Err(AppError::new(
+                    ErrorCode::IdempotencyConflict,
+                    "Idempotency-Key was already used for a different request",
+                ));
+            }
+            return match &entry.result {
+                Some((result, _)) => Ok(Claim::Replay(result.clone())),
+                None => Err(AppError::new(
+                    ErrorCode::IdempotencyConflict,
+                    "A request with this Idempotency-Key is still in progress",
+                )),
+            };
+        }
+
+        entries.insert(
+            key.to_string(),
+            Entry {
+                fingerprint: fingerprint.to_string(),
+                result: None,
+            },
+        );
+        Ok(Claim::Fresh(ClaimGuard {
+            cache: self.clone(),
+            key: key.to_string(),
+            completed: false,
+        }))
+    }
+}
+
+impl<T> IdempotencyCache<T> {
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, Entry<T>>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Holds a claimed key. Dropping it without `complete` (an error, or the
+/// request being cancelled) frees the key so the client can retry.
+pub struct ClaimGuard<T> {
+    cache: IdempotencyCache<T>,
+    key: String,
+    completed: bool,
+}
+
+impl<T> ClaimGuard<T> {
+    pub fn complete(mut self, result: T, ttl: Duration) {
+        if let Some(entry) = self.cache.lock().get_mut(&self.key) {
+            entry.result = Some((result, Instant::now() + ttl));
+        }
+        self.completed = true;
+    }
+}
+
+impl<T> Drop for ClaimGuard<T> {
+    fn drop(&mut self) {
+        if !self.completed {
+            self.cache.lock().remove(&self.key);
+        }
+    }
+}
+
+/// The request's `Idempotency-Key`, if it sent a non-empty one.
+pub fn idempotency_key(headers: &HeaderMap) -> Result<Option<&str>, AppError> {
+    let Some(value) = headers.get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(None);
+    };
+    let invalid = |message| AppError::new(ErrorCode::InvalidIdempotencyKey, message);
+    let key = value
+        .to_str()
+        .map(str::trim)
+        .map_err(|_| invalid("Idempotency-Key must be visible ASCII"))?;
+    if key.len() > MAX_KEY_LEN {
+        return Err(invalid("Idempotency-Key must be at most 255 characters"));
+    }
+    Ok(Some(key).filter(|key| !key.is_empty()))
+}
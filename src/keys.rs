@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use solana_sdk::signature::read_keypair_file;
+
+use crate::config::{self, ConfigError};
+use crate::error::{AppError, ErrorCode};
+use crate::types::decode_secret;
+
+/// Keypairs loaded from `SERVER_KEYS` files, by alias, so signing requests can
+/// name one with `keyAlias` instead of sending its secret. Read at startup and
+/// again on each reload.
+#[derive(Clone, Default)]
+pub struct ServerKeys(Arc<HashMap<String, [u8; 64]>>);
+
+impl ServerKeys {
+    /// `entries` are `alias=path` pairs, each path a Solana CLI keypair file.
+    /// A malformed entry or unreadable file fails the load.
+    pub fn load(entries: Vec<String>) -> Result<Self, ConfigError> {
+        let mut keys = HashMap::new();
+        for entry in entries {
+            let Some((alias, path)) = entry.split_once('=') else {
+                return Err(ConfigError::Invalid {
+                    key: "SERVER_KEYS",
+                    value: entry,
+                });
+            };
+            let (alias, path) = (alias.trim(), path.trim());
+            if alias.is_empty() || keys.contains_key(alias) {
+                return Err(ConfigError::Invalid {
+                    key: "SERVER_KEYS",
+                    value: entry,
+                });
+            }
+            let keypair = read_keypair_file(path).map_err(|e| ConfigError::KeyFile {
+                alias: alias.to_string(),
+                path: path.to_string(),
+                reason: e.to_string(),
+            })?;
+            keys.insert(alias.to_string(), keypair.to_bytes());
+        }
+        Ok(Self(Arc::new(keys)))
+    }
+
+    fn get(&self, alias: &str) -> Option<&[u8; 64]> {
+        self.0.get(alias)
+    }
+}
+
+/// Lists the aliases only, so the config can be logged.
+impl fmt::Debug for ServerKeys {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.0.keys()).finish()
+    }
+}
+
+/// The 64 secret-key bytes to sign with: `secret` decoded, or the server key
+/// named by `keyAlias`. Exactly one must be given.
+pub fn signing_secret(secret: Option<&str>, key_alias: Option<&str>) -> Result<Vec<u8>, AppError> {
+    match (secret, key_alias) {
+        (Some(secret), None) => decode_secret(secret),
+        (None, Some(alias)) => server_key(alias.trim()),
+        _ => Err(AppError::new(
+            ErrorCode::InvalidSecret,
+            "Provide exactly one of secret or keyAlias",
+        )),
+    }
+}
+
+fn server_key(alias: &str) -> Result<Vec<u8>, AppError> {
+    let config = config::get();
+    if !config.allow_server_keys {
+        return Err(AppError::new(
+            ErrorCode::EndpointDisabled,
+            "Signing with server keys is disabled: set ALLOW_SERVER_KEYS=true to enable it",
+        ));
+    }
+    config
+        .server_keys
+        .get(alias)
+        .map(|bytes| bytes.to_vec())
+        .ok_or_else(|| {
+            AppError::new(ErrorCode::UnknownKeyAlias, format!("Unknown keyAlias {:?}", alias))
+        })
+}
@@ -0,0 +1,25 @@
+use std::io;
+use std::net::SocketAddr;
+
+use socket2::{Domain, Protocol, Socket, TcpKeepalive, Type};
+use tokio::net::TcpListener;
+
+use crate::config::Config;
+
+/// Binds the listening socket with the configured backlog and keepalive.
+/// Accepted connections inherit keepalive from the listener on Linux;
+/// `TCP_NODELAY` is applied per connection by `axum::serve`. These settings
+/// are read once at startup, so changing them needs a restart.
+pub fn bind(addr: SocketAddr, config: &Config) -> io::Result<TcpListener> {
+    let socket = Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))?;
+    socket.set_reuse_address(true)?;
+    socket.set_nonblocking(true)?;
+    if let Some(idle) = config.tcp_keepalive {
+        socket.set_tcp_keepalive(&TcpKeepalive::new().with_time(idle))?;
+    }
+    socket.bind(&addr.into())?;
+    // listen(2) takes an int; larger values are clamped by the kernel anyway.
+    socket.listen(config.tcp_backlog.min(i32::MAX as u32) as i32)?;
+
+    TcpListener::from_std(socket.into())
+}
@@ -1,21 +1,223 @@
-use axum::{routing::post, Router};
-use tokio::net::TcpListener;
+use std::io::IsTerminal;
+use std::net::SocketAddr;
+
+use axum::{
+    error_handling::HandleErrorLayer,
+    middleware::{from_fn, from_fn_with_state},
+    routing::{get, post},
+    Router,
+};
+use tower::ServiceBuilder;
+use tower_http::catch_panic::CatchPanicLayer;
+use tracing_subscriber::{fmt::format::FmtSpan, EnvFilter};
+use endpoints::EndpointFilter;
+use middleware::echoing;
+use state::AppState;
+use types::batch_body_limit;
+mod config;
+mod endpoints;
+mod error;
 mod handlers;
+mod idempotency;
+mod keys;
+mod listener;
+mod middleware;
+mod ndjson;
+mod proto;
+mod response;
+mod rpc;
+mod state;
+mod stats;
 mod types;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let app = Router::new()
-        .route("/keypair", post(handlers::keypair::generate_keypair))
-        .route("/token/create", post(handlers::token::create_token))
-        .route("/token/mint", post(handlers::token::mint_token))
-         .route("/message/sign", post(handlers::message::sign_message))
-        .route("/message/verify", post(handlers::message::verify_message))
-        .route("/send/sol", post(handlers::transfer::send_sol))
-        .route("/send/token", post(handlers::transfer::send_token));
+    // One line per closed span: each request, and inside it the handler's
+    // span with its program id, instruction type and pubkeys. `RUST_LOG`
+    // overrides the default `info` level.
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into()))
+        .with_span_events(FmtSpan::CLOSE)
+        .with_ansi(std::io::stdout().is_terminal())
+        .init();
+
+    let state = AppState::new(config::init(config::Config::load()?));
+    let endpoints = EndpointFilter::new(&config::get());
+    let app = app(state, &endpoints);
+    endpoints.check()?;
 
-    let listener = TcpListener::bind("0.0.0.0:3000").await?;
+    let config = config::get();
+    let listener = listener::bind(SocketAddr::from(([0, 0, 0, 0], 3000)), &config)?;
     println!("Listening on {}", listener.local_addr()?);
-    axum::serve(listener, app).await?;
+    axum::serve(listener, app)
+        .tcp_nodelay(config.tcp_nodelay)
+        .await?;
     Ok(())
 }
+
+/// Every route the filter allows, with the middleware stack.
+fn app(state: AppState, endpoints: &EndpointFilter) -> Router {
+    // Only the RPC-backed routes can stall on a slow upstream, so only they
+    // carry a deadline.
+    let rpc_routes = endpoints
+        .router()
+        .route("/token/wrapped-sol-balance", echoing(post(handlers::balance::wrapped_sol_balance)))
+        .route(
+            "/token/mint-info-by-batch",
+            post(handlers::mint_info::mint_info_batch)
+                .layer(batch_body_limit(handlers::mint_info::MAX_MINT_ENTRY_BYTES)),
+        )
+        .route("/token/delegate-info", echoing(post(handlers::balance::delegate_info)))
+        .route("/token/transfer-all", echoing(post(handlers::balance::transfer_all)))
+        .route("/health/deep", get(handlers::health::deep_health))
+        .route("/health/rpc-latency", get(handlers::health::rpc_latency))
+        .route("/sysvar/clock", get(handlers::sysvar::clock))
+        .route("/sysvar/epoch-info", get(handlers::sysvar::epoch_info))
+        .route("/blockhash/is-valid", post(handlers::blockhash::is_blockhash_valid))
+        .route("/account/exists", echoing(post(handlers::account::account_exists)))
+        .route("/token/account-rent", post(handlers::token::account_rent))
+        .route(
+            "/transaction/build",
+            post(handlers::transaction::build_transaction)
+                .layer(batch_body_limit(handlers::instructions::MAX_INSTRUCTION_SPEC_BYTES)),
+        )
+        .route("/transaction/send-and-confirm", post(handlers::transaction::send_and_confirm))
+        .route("/keypair/sign-and-send", post(handlers::transaction::sign_and_send))
+        .into_router()
+        .layer(
+            ServiceBuilder::new()
+                .layer(HandleErrorLayer::new(middleware::handle_timeout))
+                .timeout(config::get().request_timeout),
+        );
+
+    endpoints
+        .router()
+        .route("/keypair", post(handlers::keypair::generate_keypair))
+        .route("/keypair/pubkey", post(handlers::keypair::generate_pubkey))
+        .route("/keypair/validate", post(handlers::keypair::validate_keypair))
+        .route("/keypair/deterministic", post(handlers::keypair::generate_deterministic_keypair))
+        .route("/keypair/recover-from-bytes", post(handlers::keypair::recover_from_bytes))
+        .route("/keypair/batch-with-seeds", post(handlers::keypair::batch_with_seeds))
+        .route("/keypair/from-mnemonic", post(handlers::keypair::from_mnemonic))
+        .route("/keypair/from-phantom", post(handlers::keypair::from_phantom))
+        .route("/keypair/grind", post(handlers::keypair::grind))
+        .route("/pubkey/validate", echoing(post(handlers::pubkey::validate_pubkey)))
+        .route("/token/create", echoing(post(handlers::token::create_token)))
+        .route("/token/mint", echoing(post(handlers::token::mint_token)))
+        .route("/token/create-and-mint", echoing(post(handlers::token::create_and_mint)))
+        .route(
+            "/token/initialize-account",
+            echoing(post(handlers::token::initialize_token_account)),
+        )
+        .route("/token/burn-and-close", echoing(post(handlers::token::burn_and_close)))
+        .route("/token/approve", echoing(post(handlers::token::approve_token)))
+        .route("/token/set-transfer-fee", echoing(post(handlers::token::set_transfer_fee)))
+        .route("/token/multisig/create", echoing(post(handlers::token::create_multisig)))
+        .route("/token/freeze-all", echoing(post(handlers::token::freeze_all)))
+        .route("/token/ata", get(handlers::token::associated_token_address))
+        .route("/rent/mint", post(handlers::rent::mint_rent))
+        .route("/token/amount/ui-to-raw", post(handlers::amount::ui_to_raw))
+        .route("/token/amount/raw-to-ui", post(handlers::amount::raw_to_ui))
+        .route("/message/sign", post(handlers::message::sign_message))
+        .route("/message/sign/solana", post(handlers::message::sign_solana_message))
+        .route("/message/verify", post(handlers::message::verify_message))
+        .route("/message/verify-any", post(handlers::message::verify_any))
+        .route("/message/verify-batch", post(handlers::message::verify_batch))
+        .route("/message/hash", post(handlers::message::hash_message))
+        .route("/transaction/inspect", post(handlers::transaction::inspect_transaction))
+        .route(
+            "/transaction/sign",
+            post(handlers::transaction::sign_transaction)
+                .layer(batch_body_limit(handlers::instructions::MAX_INSTRUCTION_SPEC_BYTES)),
+        )
+        .route(
+            "/transaction/deserialize-and-resign",
+            post(handlers::transaction::deserialize_and_resign),
+        )
+        .route("/send/sol", echoing(post(handlers::transfer::send_sol)))
+        .route("/send/token", echoing(post(handlers::transfer::send_token)))
+        .route(
+            "/instructions/batch",
+            post(handlers::instructions::batch_instructions)
+                .layer(batch_body_limit(handlers::instructions::MAX_INSTRUCTION_SPEC_BYTES)),
+        )
+        .route("/instructions/ed25519", post(handlers::precompile::ed25519_instruction))
+        .route("/instructions/secp256k1", post(handlers::precompile::secp256k1_instruction))
+        .route("/version", get(handlers::version::version))
+        .route("/stats", get(handlers::stats::stats))
+        .route("/admin/reload", post(handlers::admin::reload_config))
+        .into_router()
+        .merge(rpc_routes)
+        .route_layer(from_fn_with_state(state.stats.clone(), middleware::count_requests))
+        .fallback(handlers::not_found::not_found)
+        .with_state(state)
+        .layer(CatchPanicLayer::custom(middleware::handle_panic))
+        .layer(middleware::compression())
+        .layer(from_fn(middleware::request_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::{to_bytes, Body},
+        http::{
+            header::{CONTENT_LENGTH, CONTENT_TYPE},
+            Request, StatusCode,
+        },
+    };
+    use serde_json::{json, Value};
+    use solana_sdk::signature::{Keypair, Signer};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    async fn post_json(path: &str, body: Value) -> (StatusCode, Value) {
+        let state = AppState::for_tests();
+        let endpoints = EndpointFilter::new(&config::get());
+        let body = body.to_string();
+        let request = Request::post(path)
+            .header(CONTENT_TYPE, "application/json")
+            .header(CONTENT_LENGTH, body.len())
+            .body(Body::from(body))
+            .unwrap();
+        let response = app(state, &endpoints).oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    // There is no `/balance/sol` route; the wrapped-SOL balance is the
+    // RPC-backed balance lookup.
+    #[tokio::test]
+    async fn rpc_route_without_rpc_is_unavailable() {
+        let owner = Keypair::new().pubkey().to_string();
+        let (status, body) =
+            post_json("/token/wrapped-sol-balance", json!({ "owner": owner })).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["success"], false);
+        assert_eq!(body["code"], "RPC_NOT_CONFIGURED");
+    }
+
+    #[tokio::test]
+    async fn instruction_route_without_rpc_still_works() {
+        let from = Keypair::new().pubkey().to_string();
+        let to = Keypair::new().pubkey().to_string();
+        let (status, body) =
+            post_json("/send/sol", json!({ "from": from, "to": to, "lamports": 1000 })).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["success"], true);
+    }
+
+    #[tokio::test]
+    async fn echoing_route_echoes_pubkeys() {
+        let from = Keypair::new().pubkey().to_string();
+        let to = Keypair::new().pubkey().to_string();
+        let (status, body) = post_json(
+            "/send/sol",
+            json!({ "from": from, "to": to, "lamports": 1000, "echoInputs": true }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["inputs"], json!({ "from": from, "to": to }));
+    }
+}
@@ -0,0 +1,159 @@
+use std::any::Any;
+
+use std::str::FromStr;
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{MatchedPath, Request, State},
+    BoxError,
+    http::{header::CONTENT_LENGTH, HeaderValue},
+    middleware::{from_fn, Next},
+    response::{IntoResponse, Response},
+    routing::MethodRouter,
+};
+use serde_json::{Map, Value};
+use solana_sdk::pubkey::Pubkey;
+use tower::timeout::error::Elapsed;
+use tracing::{info_span, Instrument};
+use tower_http::compression::{
+    predicate::{NotForContentType, Predicate, SizeAbove},
+    CompressionLayer, DefaultPredicate,
+};
+use uuid::Uuid;
+
+use crate::config::MAX_BODY_BYTES;
+use crate::error::{AppError, ErrorCode};
+use crate::ndjson::NDJSON_CONTENT_TYPE;
+use crate::response::ECHOED_INPUTS;
+use crate::stats::Stats;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Responses smaller than this (a keypair, a single instruction) gain too
+/// little from compression to be worth the CPU.
+const COMPRESSION_MIN_BYTES: u16 = 1024;
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// Tags each request with an id, reusing the caller's `x-request-id` when
+/// present, and echoes it on the response. The request runs in a span that
+/// carries the id, so the handler's own span nests under it in the logs.
+pub async fn request_id(req: Request, next: Next) -> Response {
+    let id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_owned)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    let span = info_span!(
+        "request",
+        request_id = %id,
+        method = %req.method(),
+        path = %req.uri().path(),
+    );
+    let mut response = REQUEST_ID
+        .scope(id.clone(), next.run(req))
+        .instrument(span)
+        .await;
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+/// Counts each routed request, by route path and outcome, for `GET /stats`.
+/// Layered with `route_layer`, so the matched path is always known.
+pub async fn count_requests(
+    State(stats): State<Stats>,
+    path: MatchedPath,
+    req: Request,
+    next: Next,
+) -> Response {
+    let response = next.run(req).await;
+    let status = response.status();
+    stats.record(path.as_str(), !status.is_client_error() && !status.is_server_error());
+    response
+}
+
+/// Honours `"echoInputs": true` in a JSON body: the request's top-level fields
+/// that hold a pubkey are echoed under `inputs` in the success envelope, so
+/// clients firing many requests in parallel can match up the responses.
+/// Secrets never qualify, since a 64-byte key doesn't parse as a pubkey.
+///
+/// Layered only on the routes wrapped in `echoing`, so other bodies are
+/// never buffered here. Even there, only bodies with a `Content-Length`
+/// within the default body limit are inspected.
+pub async fn echo_inputs(req: Request, next: Next) -> Response {
+    let length = req
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<usize>().ok());
+    if !matches!(length, Some(1..=MAX_BODY_BYTES)) {
+        return next.run(req).await;
+    }
+
+    let (parts, body) = req.into_parts();
+    let Ok(bytes) = to_bytes(body, MAX_BODY_BYTES).await else {
+        return AppError::new(ErrorCode::InvalidMessage, "Failed to read request body")
+            .into_response();
+    };
+    let inputs = serde_json::from_slice::<Map<String, Value>>(&bytes)
+        .ok()
+        .filter(|body| body.get("echoInputs") == Some(&Value::Bool(true)))
+        .map(|body| {
+            body.into_iter()
+                .filter(|(_, value)| value.as_str().is_some_and(|s| Pubkey::from_str(s).is_ok()))
+                .collect::<Map<String, Value>>()
+        });
+
+    let req = Request::from_parts(parts, Body::from(bytes));
+    match inputs {
+        Some(inputs) => ECHOED_INPUTS.scope(inputs, next.run(req)).await,
+        None => next.run(req).await,
+    }
+}
+
+/// `handler` with `echo_inputs` layered on, for routes whose request names
+/// pubkeys worth echoing.
+pub fn echoing<S: Clone + Send + Sync + 'static>(handler: MethodRouter<S>) -> MethodRouter<S> {
+    handler.layer(from_fn(echo_inputs))
+}
+
+/// gzip/br compression for clients that send `Accept-Encoding`. NDJSON streams
+/// are left alone so each line still reaches the client as soon as it's ready.
+pub fn compression() -> CompressionLayer<impl Predicate> {
+    CompressionLayer::new().compress_when(
+        DefaultPredicate::new()
+            .and(SizeAbove::new(COMPRESSION_MIN_BYTES))
+            .and(NotForContentType::const_new(NDJSON_CONTENT_TYPE)),
+    )
+}
+
+/// Turns a handler panic into a 500 JSON error instead of a dropped connection.
+pub fn handle_panic(err: Box<dyn Any + Send + 'static>) -> Response {
+    let detail = err
+        .downcast_ref::<String>()
+        .map(String::as_str)
+        .or_else(|| err.downcast_ref::<&str>().copied())
+        .unwrap_or("unknown panic payload");
+    let request_id = REQUEST_ID
+        .try_with(Clone::clone)
+        .unwrap_or_else(|_| "-".to_string());
+
+    tracing::error!(request_id = %request_id, %detail, "handler panicked");
+    AppError::new(ErrorCode::InternalError, "internal error").into_response()
+}
+
+/// Maps errors from the timeout layer onto the standard JSON error shape.
+pub async fn handle_timeout(err: BoxError) -> Response {
+    if err.is::<Elapsed>() {
+        AppError::new(ErrorCode::RequestTimeout, "Request timed out").into_response()
+    } else {
+        AppError::new(ErrorCode::InternalError, format!("Unhandled error: {}", err))
+            .into_response()
+    }
+}
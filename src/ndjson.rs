@@ -0,0 +1,49 @@
+use std::future::ready;
+
+use axum::{
+    body::{Body, Bytes},
+    http::{header, HeaderValue},
+    response::{IntoResponse, Response},
+};
+use futures_util::{Stream, StreamExt};
+use serde::Serialize;
+
+use crate::error::AppError;
+
+pub const NDJSON_CONTENT_TYPE: &str = "application/x-ndjson";
+
+/// Streams `items` as newline-delimited JSON, one object per line, so large
+/// batches are never buffered whole. The status is sent before any item is
+/// produced, so a failed item is written as an error-body line and ends the
+/// stream.
+pub fn ndjson_response<S, T>(items: S) -> Response
+where
+    S: Stream<Item = Result<T, AppError>> + Send + 'static,
+    T: Serialize,
+{
+    let lines = items.scan(false, |failed, item| {
+        if *failed {
+            return ready(None);
+        }
+        let line = match item {
+            Ok(value) => serde_json::to_vec(&value),
+            Err(e) => {
+                *failed = true;
+                serde_json::to_vec(&e.body())
+            }
+        };
+        ready(Some(line.map(|mut line| {
+            line.push(b'\n');
+            Bytes::from(line)
+        })))
+    });
+
+    (
+        [(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static(NDJSON_CONTENT_TYPE),
+        )],
+        Body::from_stream(lines),
+    )
+        .into_response()
+}
@@ -0,0 +1,176 @@
+use std::convert::Infallible;
+
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header, request::Parts, HeaderValue},
+    response::{IntoResponse, Response},
+};
+use prost::Message;
+
+use crate::ndjson::NDJSON_CONTENT_TYPE;
+use crate::response::ApiResponse;
+use crate::types::{BuildOutcome, InstructionSequence, TokenInstructionResponse, WithCompiled};
+
+pub const PROTOBUF_CONTENT_TYPE: &str = "application/x-protobuf";
+
+// Hand-written equivalents of the messages in proto/instruction.proto, so the
+// build does not need `protoc`. Keep the field tags in sync with the schema.
+
+#[derive(Clone, PartialEq, Message)]
+pub struct AccountMetaProto {
+    #[prost(string, tag = "1")]
+    pub pubkey: String,
+    #[prost(bool, tag = "2")]
+    pub is_signer: bool,
+    #[prost(bool, tag = "3")]
+    pub is_writable: bool,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct TokenInstructionProto {
+    #[prost(string, tag = "1")]
+    pub program_id: String,
+    #[prost(message, repeated, tag = "2")]
+    pub accounts: Vec<AccountMetaProto>,
+    #[prost(string, tag = "3")]
+    pub instruction_data: String,
+    #[prost(string, tag = "4")]
+    pub instruction_type: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct InstructionSequenceProto {
+    #[prost(message, repeated, tag = "1")]
+    pub instructions: Vec<TokenInstructionProto>,
+}
+
+/// Response data that has a protobuf encoding. `None` means this value has no
+/// protobuf form (e.g. a dry-run result) and is sent as JSON instead.
+pub trait ToProto {
+    fn encode_proto(&self) -> Option<Vec<u8>>;
+}
+
+impl From<&TokenInstructionResponse> for TokenInstructionProto {
+    fn from(ix: &TokenInstructionResponse) -> Self {
+        Self {
+            program_id: ix.program_id.clone(),
+            accounts: ix
+                .accounts
+                .iter()
+                .map(|meta| AccountMetaProto {
+                    pubkey: meta.pubkey.clone(),
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                })
+                .collect(),
+            instruction_data: ix.instruction_data.clone(),
+            instruction_type: ix
+                .instruction_type
+                .map_or_else(String::new, |t| t.as_str().to_string()),
+        }
+    }
+}
+
+impl ToProto for TokenInstructionResponse {
+    fn encode_proto(&self) -> Option<Vec<u8>> {
+        Some(TokenInstructionProto::from(self).encode_to_vec())
+    }
+}
+
+impl ToProto for InstructionSequence {
+    fn encode_proto(&self) -> Option<Vec<u8>> {
+        let sequence = InstructionSequenceProto {
+            instructions: self.instructions.iter().map(Into::into).collect(),
+        };
+        Some(sequence.encode_to_vec())
+    }
+}
+
+/// The protobuf schema has no compiled form, so only the response is encoded.
+impl<T: ToProto> ToProto for WithCompiled<T> {
+    fn encode_proto(&self) -> Option<Vec<u8>> {
+        self.response.encode_proto()
+    }
+}
+
+impl<T: ToProto> ToProto for BuildOutcome<T> {
+    fn encode_proto(&self) -> Option<Vec<u8>> {
+        match self {
+            BuildOutcome::Built(data) => data.encode_proto(),
+            BuildOutcome::DryRun(_) => None,
+        }
+    }
+}
+
+/// The response format picked from the request's `Accept` header. Anything
+/// other than `application/x-protobuf` or `application/x-ndjson` gets JSON,
+/// as does any handler that doesn't support the requested format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResponseFormat {
+    Json,
+    Protobuf,
+    /// Newline-delimited JSON; only the batch endpoints stream it.
+    Ndjson,
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for ResponseFormat {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let accepts = |wanted: &str| {
+            parts
+                .headers
+                .get_all(header::ACCEPT)
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .flat_map(|value| value.split(','))
+                .filter_map(|media| media.split(';').next())
+                .any(|media| media.trim().eq_ignore_ascii_case(wanted))
+        };
+
+        Ok(if accepts(PROTOBUF_CONTENT_TYPE) {
+            ResponseFormat::Protobuf
+        } else if accepts(NDJSON_CONTENT_TYPE) {
+            ResponseFormat::Ndjson
+        } else {
+            ResponseFormat::Json
+        })
+    }
+}
+
+impl ResponseFormat {
+    /// Encodes `data` as protobuf when requested and available; otherwise
+    /// returns the usual JSON envelope.
+    pub fn negotiate<T: ToProto>(self, data: T) -> Negotiated<T> {
+        if self == ResponseFormat::Protobuf {
+            if let Some(bytes) = data.encode_proto() {
+                return Negotiated::Protobuf(bytes);
+            }
+        }
+        Negotiated::Json(data.into())
+    }
+}
+
+/// A handler response that is either the JSON envelope or raw protobuf bytes.
+pub enum Negotiated<T> {
+    Json(ApiResponse<T>),
+    Protobuf(Vec<u8>),
+}
+
+impl<T: serde::Serialize> IntoResponse for Negotiated<T> {
+    fn into_response(self) -> Response {
+        match self {
+            Negotiated::Json(json) => json.into_response(),
+            Negotiated::Protobuf(bytes) => (
+                [(
+                    header::CONTENT_TYPE,
+                    HeaderValue::from_static(PROTOBUF_CONTENT_TYPE),
+                )],
+                bytes,
+            )
+                .into_response(),
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use axum::{
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::error::AppError;
+
+tokio::task_local! {
+    /// Pubkeys from a request that asked for `echoInputs`, set by
+    /// `middleware::echo_inputs` for the duration of the handler.
+    pub static ECHOED_INPUTS: Map<String, Value>;
+}
+
+/// The `{"success": true, "data": ...}` envelope. It can only be built from
+/// success data via `From`, and failures go through `AppError`, so `success`
+/// always agrees with the outcome.
+#[derive(Debug, Serialize)]
+pub struct ApiResponse<T> {
+    success: bool,
+    data: T,
+    /// The request's top-level pubkey fields, when it set `echoInputs`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    inputs: Option<Map<String, Value>>,
+}
+
+impl<T> From<T> for ApiResponse<T> {
+    fn from(data: T) -> Self {
+        Self {
+            success: true,
+            data,
+            inputs: None,
+        }
+    }
+}
+
+#[cfg(test)]
+impl<T> ApiResponse<T> {
+    pub fn into_data(self) -> T {
+        self.data
+    }
+}
+
+impl<T: Serialize> IntoResponse for ApiResponse<T> {
+    fn into_response(mut self) -> Response {
+        self.inputs = ECHOED_INPUTS.try_with(Map::clone).ok();
+        Json(self).into_response()
+    }
+}
+
+/// What a JSON handler returns: the success envelope or an `AppError`.
+pub type HandlerResult<T> = Result<ApiResponse<T>, AppError>;
@@ -0,0 +1,253 @@
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use solana_client::client_error::{
+    reqwest::{
+        header::{CONTENT_TYPE, RETRY_AFTER},
+        Client, StatusCode,
+    },
+    ClientError, ClientErrorKind, Result as ClientResult,
+};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_client::RpcClientConfig;
+use solana_client::rpc_custom_error::JSON_RPC_SERVER_ERROR_SEND_TRANSACTION_PREFLIGHT_FAILURE;
+use solana_client::rpc_request::{RpcError, RpcRequest, RpcResponseErrorData};
+use solana_client::rpc_sender::{RpcSender, RpcTransportStats};
+use solana_sdk::{account::Account, commitment_config::CommitmentConfig, pubkey::Pubkey};
+
+use crate::config;
+use crate::error::{AppError, ErrorCode};
+
+/// Same as the client's default HTTP timeout.
+const RPC_HTTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// An RPC client that counts each HTTP call it makes in `calls`.
+pub fn rpc_client(url: String, calls: Arc<AtomicU64>) -> RpcClient {
+    RpcClient::new_sender(
+        RateLimitAwareSender::new(url, calls),
+        RpcClientConfig::with_commitment(CommitmentConfig::default()),
+    )
+}
+
+pub fn rpc_error(err: ClientError) -> AppError {
+    if let Some(limited) = rate_limited(&err) {
+        let mut error =
+            AppError::new(ErrorCode::RpcRateLimited, "RPC node is rate limiting requests");
+        error.retry_after = limited.retry_after.clone();
+        return error;
+    }
+    AppError::new(ErrorCode::RpcError, format!("RPC request failed: {}", err))
+}
+
+/// Reads one account, `None` if it doesn't exist. Unlike
+/// `RpcClient::get_account_with_commitment`, which flattens its errors into a
+/// message, this keeps them intact so `rpc_error` can still spot a 429.
+pub async fn fetch_account(
+    rpc: &RpcClient,
+    pubkey: &Pubkey,
+    commitment: CommitmentConfig,
+) -> ClientResult<Option<Account>> {
+    let mut accounts = rpc
+        .get_multiple_accounts_with_commitment(&[*pubkey], commitment)
+        .await?
+        .value;
+    Ok(accounts.pop().flatten())
+}
+
+/// A request's `timeoutMs`, clamped to `MAX_RPC_TIMEOUT_MS`. `None` leaves
+/// only the route's request timeout in force.
+pub fn requested_timeout(timeout_ms: Option<u64>) -> Result<Option<Duration>, AppError> {
+    match timeout_ms {
+        None => Ok(None),
+        Some(0) => Err(AppError::new(
+            ErrorCode::InvalidTimeout,
+            "timeoutMs must be greater than zero",
+        )),
+        Some(ms) => Ok(Some(Duration::from_millis(ms).min(config::get().max_rpc_timeout))),
+    }
+}
+
+/// Awaits one RPC call under the request's `timeoutMs`, failing with a 504
+/// that names the deadline if it runs out first.
+pub async fn within<F: Future>(timeout_ms: Option<u64>, call: F) -> Result<F::Output, AppError> {
+    let Some(timeout) = requested_timeout(timeout_ms)? else {
+        return Ok(call.await);
+    };
+    tokio::time::timeout(timeout, call).await.map_err(|_| {
+        AppError::new(
+            ErrorCode::RequestTimeout,
+            format!("RPC call did not finish within timeoutMs ({} ms)", timeout.as_millis()),
+        )
+    })
+}
+
+fn rate_limited(err: &ClientError) -> Option<&RateLimited> {
+    match err.kind() {
+        ClientErrorKind::Io(io) => io.get_ref()?.downcast_ref(),
+        _ => None,
+    }
+}
+
+/// An upstream 429, carried through `ClientError` as an I/O error.
+#[derive(Debug)]
+struct RateLimited {
+    retry_after: Option<String>,
+}
+
+impl fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("429 Too Many Requests")
+    }
+}
+
+impl std::error::Error for RateLimited {}
+
+/// The client's own HTTP sender retries a 429 several times and then drops
+/// `Retry-After`. This one returns it straight away so the caller can pass the
+/// upstream's back-off on to its client instead of holding the request open.
+struct RateLimitAwareSender {
+    client: Client,
+    url: String,
+    request_id: AtomicU64,
+    calls: Arc<AtomicU64>,
+}
+
+impl RateLimitAwareSender {
+    fn new(url: String, calls: Arc<AtomicU64>) -> Self {
+        Self {
+            client: Client::builder()
+                .timeout(RPC_HTTP_TIMEOUT)
+                .pool_idle_timeout(RPC_HTTP_TIMEOUT)
+                .build()
+                .expect("build rpc http client"),
+            url,
+            request_id: AtomicU64::new(0),
+            calls,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct RpcErrorObject {
+    code: i64,
+    message: String,
+}
+
+#[async_trait]
+impl RpcSender for RateLimitAwareSender {
+    async fn send(
+        &self,
+        request: RpcRequest,
+        params: serde_json::Value,
+    ) -> ClientResult<serde_json::Value> {
+        let request_id = self.request_id.fetch_add(1, Ordering::Relaxed);
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        let response = self
+            .client
+            .post(&self.url)
+            .header(CONTENT_TYPE, "application/json")
+            .body(request.build_request_json(request_id, params).to_string())
+            .send()
+            .await?;
+
+        if response.status() == StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = response
+                .headers()
+                .get(RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string);
+            return Err(io::Error::other(RateLimited { retry_after }).into());
+        }
+        let mut json = response
+            .error_for_status()?
+            .json::<serde_json::Value>()
+            .await?;
+
+        if !json["error"].is_object() {
+            return Ok(json["result"].take());
+        }
+        let error = serde_json::from_value::<RpcErrorObject>(json["error"].clone()).map_err(|e| {
+            ClientError::from(RpcError::RpcRequestError(format!(
+                "Failed to deserialize RPC error response: {} [{}]",
+                json["error"], e
+            )))
+        })?;
+        // Keep the simulation result of a failed preflight, which
+        // `get_transaction_error` reads the program error from.
+        let data = match error.code {
+            JSON_RPC_SERVER_ERROR_SEND_TRANSACTION_PREFLIGHT_FAILURE => {
+                serde_json::from_value(json["error"]["data"].take())
+                    .map(RpcResponseErrorData::SendTransactionPreflightFailure)
+                    .unwrap_or(RpcResponseErrorData::Empty)
+            }
+            _ => RpcResponseErrorData::Empty,
+        };
+        Err(RpcError::RpcResponseError {
+            code: error.code,
+            message: error.message,
+            data,
+        }
+        .into())
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        RpcTransportStats::default()
+    }
+
+    fn url(&self) -> String {
+        self.url.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        http::StatusCode as HttpStatus,
+        response::{IntoResponse, Response},
+        routing::post,
+        Json, Router,
+    };
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    /// An RPC node that answers every call with a 429 and `Retry-After: 7`,
+    /// except the `getVersion` the client sends first to pick its commitment
+    /// format.
+    async fn rate_limited_node() -> String {
+        async fn handle(Json(request): Json<Value>) -> Response {
+            if request["method"] == "getVersion" {
+                let result = json!({ "solana-core": "1.18.26", "feature-set": 0 });
+                return Json(json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }))
+                    .into_response();
+            }
+            (HttpStatus::TOO_MANY_REQUESTS, [("retry-after", "7")]).into_response()
+        }
+        let app = Router::new().route("/", post(handle));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        url
+    }
+
+    #[tokio::test]
+    async fn rate_limited_call_keeps_retry_after() {
+        let calls = Arc::new(AtomicU64::new(0));
+        let rpc = rpc_client(rate_limited_node().await, calls.clone());
+
+        let err = rpc_error(rpc.get_slot().await.unwrap_err());
+
+        assert_eq!(err.code, ErrorCode::RpcRateLimited);
+        assert_eq!(err.code.status(), HttpStatus::TOO_MANY_REQUESTS);
+        assert_eq!(err.retry_after.as_deref(), Some("7"));
+        // `getVersion`, then the one rate-limited call, passed straight
+        // through rather than retried.
+        assert_eq!(calls.load(Ordering::Relaxed), 2);
+    }
+}
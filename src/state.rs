@@ -0,0 +1,81 @@
+use std::sync::{Arc, RwLock};
+
+use solana_client::nonblocking::rpc_client::RpcClient;
+use tokio::sync::Semaphore;
+
+use crate::config::{Config, SharedConfig};
+use crate::error::{AppError, ErrorCode};
+use crate::handlers::transaction::SendAndConfirmData;
+use crate::idempotency::IdempotencyCache;
+use crate::rpc;
+use crate::stats::Stats;
+
+#[derive(Clone)]
+pub struct AppState {
+    /// The live config; `POST /admin/reload` swaps it in place.
+    pub config: SharedConfig,
+    /// `None` when `RPC_URL` is unset; the offline endpoints work regardless.
+    /// Rebuilt by a reload that changes `RPC_URL`.
+    rpc: Arc<RwLock<Option<Arc<RpcClient>>>>,
+    /// Results of `/transaction/send-and-confirm`, by `Idempotency-Key`.
+    pub submissions: IdempotencyCache<SendAndConfirmData>,
+    /// Counters behind `GET /stats`.
+    pub stats: Stats,
+    /// One permit per `/keypair/grind` allowed to run at once.
+    pub grinds: Arc<Semaphore>,
+}
+
+impl AppState {
+    pub fn new(config: SharedConfig) -> Self {
+        let stats = Stats::default();
+        let rpc = rpc_client(&read(&config).rpc_url, &stats);
+        let grinds = Arc::new(Semaphore::new(read(&config).grind_max_concurrent));
+        Self {
+            config,
+            rpc: Arc::new(RwLock::new(rpc)),
+            submissions: IdempotencyCache::new(),
+            stats,
+            grinds,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn for_tests() -> Self {
+        Self::new(crate::config::init_for_tests())
+    }
+
+    /// The RPC client, or a 503 for endpoints that need one when none is configured.
+    pub fn rpc(&self) -> Result<Arc<RpcClient>, AppError> {
+        read(&self.rpc).clone().ok_or_else(|| {
+            AppError::new(
+                ErrorCode::RpcNotConfigured,
+                "RPC not configured: set RPC_URL to enable this endpoint",
+            )
+        })
+    }
+
+    /// Swaps in a freshly loaded config, rebuilding the RPC client only if
+    /// `RPC_URL` changed so in-flight requests keep their client.
+    pub fn apply(&self, config: Config) {
+        if read(&self.config).rpc_url != config.rpc_url {
+            *write(&self.rpc) = rpc_client(&config.rpc_url, &self.stats);
+        }
+        *write(&self.config) = Arc::new(config);
+    }
+}
+
+fn rpc_client(url: &Option<String>, stats: &Stats) -> Option<Arc<RpcClient>> {
+    url.clone()
+        .map(|url| Arc::new(rpc::rpc_client(url, stats.rpc_calls.clone())))
+}
+
+// A panic while holding one of these locks can't leave the swapped `Arc`
+// half-written, so a poisoned lock is still safe to use.
+
+fn read<T: Clone>(lock: &RwLock<T>) -> T {
+    lock.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+fn write<T>(lock: &RwLock<T>) -> std::sync::RwLockWriteGuard<'_, T> {
+    lock.write().unwrap_or_else(|e| e.into_inner())
+}
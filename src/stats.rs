@@ -0,0 +1,93 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+use serde::Serialize;
+
+/// In-process request and RPC counters behind `GET /stats`. Per process and
+/// reset by a restart, like the idempotency cache.
+#[derive(Clone, Default)]
+pub struct Stats {
+    /// Routed requests; paths that fall through to the 404 aren't counted.
+    pub requests: Arc<AtomicU64>,
+    /// HTTP calls made to the RPC node, shared with the RPC client so it
+    /// survives a reload that rebuilds the client.
+    pub rpc_calls: Arc<AtomicU64>,
+    /// By route path, such as `/token/mint`.
+    endpoints: Arc<RwLock<HashMap<String, Arc<EndpointCounters>>>>,
+}
+
+#[derive(Default)]
+struct EndpointCounters {
+    success: AtomicU64,
+    failure: AtomicU64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatsSnapshot {
+    pub requests: u64,
+    pub rpc_calls: u64,
+    pub endpoints: BTreeMap<String, EndpointSnapshot>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EndpointSnapshot {
+    pub success: u64,
+    pub failure: u64,
+}
+
+impl Stats {
+    /// Records one finished request to `path`; any status below 400 counts
+    /// as a success.
+    pub fn record(&self, path: &str, success: bool) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        let counters = self.counters(path);
+        let counter = if success { &counters.success } else { &counters.failure };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// The current counts; with `reset`, each counter is zeroed as it is read,
+    /// so no increment is lost between the read and the reset.
+    pub fn snapshot(&self, reset: bool) -> StatsSnapshot {
+        let take = |counter: &AtomicU64| {
+            if reset {
+                counter.swap(0, Ordering::Relaxed)
+            } else {
+                counter.load(Ordering::Relaxed)
+            }
+        };
+        let endpoints = self
+            .endpoints
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .map(|(path, counters)| {
+                let snapshot = EndpointSnapshot {
+                    success: take(&counters.success),
+                    failure: take(&counters.failure),
+                };
+                (path.clone(), snapshot)
+            })
+            .collect();
+        StatsSnapshot {
+            requests: take(&self.requests),
+            rpc_calls: take(&self.rpc_calls),
+            endpoints,
+        }
+    }
+
+    fn counters(&self, path: &str) -> Arc<EndpointCounters> {
+        let endpoints = self.endpoints.read().unwrap_or_else(|e| e.into_inner());
+        if let Some(counters) = endpoints.get(path) {
+            return counters.clone();
+        }
+        drop(endpoints);
+        self.endpoints
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(path.to_string())
+            .or_default()
+            .clone()
+    }
+}
@@ -0,0 +1,533 @@
+use std::str::FromStr;
+
+use axum::extract::DefaultBodyLimit;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Serialize, Serializer};
+use solana_program::{instruction::Instruction, message::Message, pubkey::Pubkey};
+use solana_sdk::commitment_config::CommitmentConfig;
+
+use crate::config;
+use crate::error::{AppError, ErrorCode};
+
+/// Parses a base58 pubkey, naming the offending request field on failure.
+/// Surrounding whitespace, such as a trailing newline from a paste, is ignored.
+pub fn parse_pubkey(value: &str, field: &str) -> Result<Pubkey, AppError> {
+    Pubkey::from_str(value.trim())
+        .map_err(|_| AppError::new(ErrorCode::InvalidPubkey, format!("Invalid {} pubkey", field)))
+}
+
+/// Parses a request's `mint`, refusing mints outside `MINT_WHITELIST` when it
+/// is set. Shared by the token endpoints that build instructions for a mint.
+pub fn parse_mint(value: &str) -> Result<Pubkey, AppError> {
+    let mint = parse_pubkey(value, "mint")?;
+    match &config::get().mint_whitelist {
+        Some(allowed) if !allowed.contains(&mint) => Err(AppError::new(
+            ErrorCode::MintNotAllowed,
+            format!("mint not allowed: {} is not in MINT_WHITELIST", mint),
+        )),
+        _ => Ok(mint),
+    }
+}
+
+/// Decodes a base58 secret key, requiring the 64-byte `secret || public` layout.
+/// Surrounding whitespace is ignored, as for pubkeys.
+pub fn decode_secret(secret: &str) -> Result<Vec<u8>, AppError> {
+    let secret = secret.trim();
+    match bs58::decode(secret).into_vec() {
+        Ok(bytes) if bytes.len() == 64 => Ok(bytes),
+        // Pasting the base64 form is a common mistake; say so instead of
+        // just calling it malformed.
+        Err(_) if STANDARD.decode(secret).is_ok_and(|bytes| bytes.len() == 64) => {
+            Err(AppError::new(
+                ErrorCode::InvalidSecret,
+                "secret looks base64-encoded; this endpoint expects base58",
+            ))
+        }
+        _ => Err(AppError::new(
+            ErrorCode::InvalidSecret,
+            "Invalid or malformed secret key (expected 64-byte base58)",
+        )),
+    }
+}
+
+/// `?dryRun=true` asks an instruction-building handler to validate its input
+/// and stop before constructing the instruction.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DryRunQuery {
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DryRunResponse {
+    pub valid: bool,
+}
+
+/// `?compiled=true` adds the compiled (indexed) form of the built
+/// instructions to an instruction-building response.
+#[derive(Debug, Default, Deserialize)]
+pub struct CompiledQuery {
+    #[serde(default)]
+    pub compiled: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum BuildOutcome<T> {
+    Built(T),
+    DryRun(DryRunResponse),
+}
+
+impl<T> BuildOutcome<T> {
+    /// Outcome for a dry run whose validation passed.
+    pub fn validated() -> Self {
+        BuildOutcome::DryRun(DryRunResponse { valid: true })
+    }
+}
+
+/// Rejects batches larger than `MAX_BATCH_SIZE`, shared by every batch endpoint.
+pub fn ensure_batch_size(len: usize) -> Result<(), AppError> {
+    let max = config::get().max_batch_size;
+    if len > max {
+        return Err(AppError::new(
+            ErrorCode::BatchTooLarge,
+            format!("Batch too large: {} items exceeds the limit of {}", len, max),
+        ));
+    }
+    Ok(())
+}
+
+/// Room for the request envelope around a batch's items.
+const BATCH_BODY_OVERHEAD: usize = 4 * 1024;
+
+/// Body-size cap for a batch route whose items serialize to at most
+/// `item_bytes` each. Layered on the route, it refuses an oversized array
+/// while the body is still being read, before any of it is parsed; the
+/// item-count check still runs afterwards. Read at startup, so a change to
+/// `MAX_BATCH_SIZE` needs a restart to move it.
+pub fn batch_body_limit(item_bytes: usize) -> DefaultBodyLimit {
+    DefaultBodyLimit::max(
+        config::get()
+            .max_batch_size
+            .saturating_mul(item_bytes)
+            .saturating_add(BATCH_BODY_OVERHEAD),
+    )
+}
+
+/// `a + b` for token/lamport amounts, failing instead of wrapping.
+pub fn checked_add_amount(a: u64, b: u64) -> Result<u64, AppError> {
+    a.checked_add(b).ok_or_else(amount_overflow)
+}
+
+/// `a * b` for token/lamport amounts, failing instead of wrapping.
+pub fn checked_mul_amount(a: u64, b: u64) -> Result<u64, AppError> {
+    a.checked_mul(b).ok_or_else(amount_overflow)
+}
+
+fn amount_overflow() -> AppError {
+    AppError::new(ErrorCode::AmountOverflow, "amount overflow")
+}
+
+/// Which SPL token program a token instruction targets. Accepted as `token`
+/// (or `spl-token`), `token-2022`, or either program's id.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TokenProgram {
+    #[default]
+    #[serde(
+        rename = "token",
+        alias = "spl-token",
+        alias = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
+    )]
+    Token,
+    #[serde(rename = "token-2022", alias = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb")]
+    Token2022,
+}
+
+impl TokenProgram {
+    pub fn id(&self) -> Pubkey {
+        match self {
+            TokenProgram::Token => spl_token::id(),
+            TokenProgram::Token2022 => spl_token_2022::id(),
+        }
+    }
+}
+
+impl FromStr for TokenProgram {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "token" | "spl-token" => Ok(TokenProgram::Token),
+            "token-2022" => Ok(TokenProgram::Token2022),
+            _ => match Pubkey::from_str(value) {
+                Ok(id) if id == spl_token::id() => Ok(TokenProgram::Token),
+                Ok(id) if id == spl_token_2022::id() => Ok(TokenProgram::Token2022),
+                _ => Err(()),
+            },
+        }
+    }
+}
+
+/// The program a request asked for, or the server-wide `DEFAULT_TOKEN_PROGRAM`.
+pub fn resolve_token_program(requested: Option<TokenProgram>) -> Pubkey {
+    requested
+        .unwrap_or(config::get().default_token_program)
+        .id()
+}
+
+/// Text encoding for binary fields, shared by every endpoint that lets the
+/// client choose one (`"base58"`, `"base64"` or `"hex"`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Encoding {
+    Base58,
+    #[default]
+    Base64,
+    Hex,
+}
+
+impl Encoding {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Encoding::Base58 => "base58",
+            Encoding::Base64 => "base64",
+            Encoding::Hex => "hex",
+        }
+    }
+
+    pub fn encode(&self, bytes: &[u8]) -> String {
+        match self {
+            Encoding::Base58 => bs58::encode(bytes).into_string(),
+            Encoding::Base64 => STANDARD.encode(bytes),
+            Encoding::Hex => hex::encode(bytes),
+        }
+    }
+
+    /// Decodes `value`; hex may carry a `0x` prefix.
+    pub fn decode(&self, value: &str) -> Result<Vec<u8>, AppError> {
+        let decoded = match self {
+            Encoding::Base58 => bs58::decode(value).into_vec().ok(),
+            Encoding::Base64 => STANDARD.decode(value).ok(),
+            Encoding::Hex => hex::decode(value.strip_prefix("0x").unwrap_or(value)).ok(),
+        };
+        decoded.ok_or_else(|| {
+            AppError::new(
+                ErrorCode::InvalidEncoding,
+                format!("Invalid {} data", self.name()),
+            )
+        })
+    }
+}
+
+/// How a request's `message` text maps to the bytes that get signed:
+/// `"utf8"` (the default) signs the text itself, `"base64"` and `"hex"` sign
+/// the bytes it decodes to, which needn't be valid UTF-8.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageEncoding {
+    #[default]
+    Utf8,
+    Base64,
+    Hex,
+}
+
+impl MessageEncoding {
+    pub fn decode(&self, message: &str) -> Result<Vec<u8>, AppError> {
+        match self {
+            MessageEncoding::Utf8 => Ok(message.as_bytes().to_vec()),
+            MessageEncoding::Base64 => Encoding::Base64.decode(message),
+            MessageEncoding::Hex => Encoding::Hex.decode(message),
+        }
+    }
+}
+
+/// Commitment level a client can ask an RPC-backed endpoint to read at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Commitment {
+    Processed,
+    Confirmed,
+    Finalized,
+}
+
+impl From<Commitment> for CommitmentConfig {
+    fn from(commitment: Commitment) -> Self {
+        match commitment {
+            Commitment::Processed => CommitmentConfig::processed(),
+            Commitment::Confirmed => CommitmentConfig::confirmed(),
+            Commitment::Finalized => CommitmentConfig::finalized(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct AccountMeta {
+    pub pubkey: String,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+/// Which instruction a response holds, so clients can tell instructions apart
+/// without tracking the endpoint that built them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstructionType {
+    SystemTransfer,
+    SystemCreateAccount,
+    InitializeMint,
+    InitializeMintCloseAuthority,
+    InitializeTransferHook,
+    InitializeAccount,
+    InitializeAccount3,
+    InitializeMultisig,
+    MintTo,
+    SplTransfer,
+    SplTransferChecked,
+    BurnChecked,
+    CloseAccount,
+    FreezeAccount,
+    Approve,
+    ApproveChecked,
+    SetTransferFee,
+    CreateAssociatedTokenAccount,
+    CreateAssociatedTokenAccountIdempotent,
+    Memo,
+    Ed25519Verify,
+    Secp256k1Verify,
+}
+
+impl InstructionType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            InstructionType::SystemTransfer => "systemTransfer",
+            InstructionType::SystemCreateAccount => "systemCreateAccount",
+            InstructionType::InitializeMint => "initializeMint",
+            InstructionType::InitializeMintCloseAuthority => "initializeMintCloseAuthority",
+            InstructionType::InitializeTransferHook => "initializeTransferHook",
+            InstructionType::InitializeAccount => "initializeAccount",
+            InstructionType::InitializeAccount3 => "initializeAccount3",
+            InstructionType::InitializeMultisig => "initializeMultisig",
+            InstructionType::MintTo => "mintTo",
+            InstructionType::SplTransfer => "splTransfer",
+            InstructionType::SplTransferChecked => "splTransferChecked",
+            InstructionType::BurnChecked => "burnChecked",
+            InstructionType::CloseAccount => "closeAccount",
+            InstructionType::FreezeAccount => "freezeAccount",
+            InstructionType::Approve => "approve",
+            InstructionType::ApproveChecked => "approveChecked",
+            InstructionType::SetTransferFee => "setTransferFee",
+            InstructionType::CreateAssociatedTokenAccount => "createAssociatedTokenAccount",
+            InstructionType::CreateAssociatedTokenAccountIdempotent => {
+                "createAssociatedTokenAccountIdempotent"
+            }
+            InstructionType::Memo => "memo",
+            InstructionType::Ed25519Verify => "ed25519Verify",
+            InstructionType::Secp256k1Verify => "secp256k1Verify",
+        }
+    }
+}
+
+impl Serialize for InstructionType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct TokenInstructionResponse {
+    /// Absent for instructions this server didn't build, such as those listed
+    /// by `/transaction/inspect`.
+    #[serde(rename = "instructionType", skip_serializing_if = "Option::is_none")]
+    pub instruction_type: Option<InstructionType>,
+    pub program_id: String,
+    pub accounts: Vec<AccountMeta>,
+    pub instruction_data: String,
+}
+
+/// Several instructions meant to be sent together, in order.
+#[derive(Debug, Serialize)]
+pub struct InstructionSequence {
+    pub instructions: Vec<TokenInstructionResponse>,
+    /// Each instruction's `instructionType`, in order, so the sequence can be
+    /// checked at a glance, e.g. `["systemCreateAccount", "initializeMint"]`.
+    pub steps: Vec<&'static str>,
+}
+
+impl InstructionSequence {
+    /// Pairs each built instruction with its type.
+    pub fn new<'a>(
+        instructions: impl IntoIterator<Item = (InstructionType, &'a Instruction)>,
+    ) -> Self {
+        let (steps, instructions) = instructions
+            .into_iter()
+            .map(|(instruction_type, ix)| {
+                (
+                    instruction_type.as_str(),
+                    instruction_to_response(ix, instruction_type, Encoding::Base64),
+                )
+            })
+            .unzip();
+        Self { instructions, steps }
+    }
+}
+
+/// Converts a built instruction into the response shape shared by every
+/// instruction-building endpoint.
+pub fn instruction_to_response(
+    ix: &Instruction,
+    instruction_type: InstructionType,
+    encoding: Encoding,
+) -> TokenInstructionResponse {
+    let accounts = ix
+        .accounts
+        .iter()
+        .map(|meta| AccountMeta {
+            pubkey: meta.pubkey.to_string(),
+            is_signer: meta.is_signer,
+            is_writable: meta.is_writable,
+        })
+        .collect();
+
+    TokenInstructionResponse {
+        instruction_type: Some(instruction_type),
+        program_id: ix.program_id.to_string(),
+        accounts,
+        instruction_data: encoding.encode(&ix.data),
+    }
+}
+
+/// A response plus, when `?compiled=true` was given, its compiled form.
+#[derive(Debug, Serialize)]
+pub struct WithCompiled<T> {
+    #[serde(flatten)]
+    pub response: T,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compiled: Option<CompiledMessage>,
+}
+
+/// The instructions as a legacy `Message` compiles them: de-duplicated,
+/// ordered account keys and per-instruction indexes into that list.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompiledMessage {
+    pub num_required_signatures: u8,
+    pub num_readonly_signed_accounts: u8,
+    pub num_readonly_unsigned_accounts: u8,
+    pub account_keys: Vec<String>,
+    pub instructions: Vec<CompiledInstructionData>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompiledInstructionData {
+    pub program_id_index: u8,
+    pub accounts: Vec<u8>,
+    pub data: String,
+}
+
+/// Wraps `response`, compiling `instructions` only when the client asked.
+/// `payer`, if given, is placed first as the fee payer.
+pub fn with_compiled<T>(
+    response: T,
+    query: &CompiledQuery,
+    instructions: &[Instruction],
+    payer: Option<&Pubkey>,
+) -> WithCompiled<T> {
+    let compiled = query.compiled.then(|| {
+        let message = Message::new(instructions, payer);
+        CompiledMessage {
+            num_required_signatures: message.header.num_required_signatures,
+            num_readonly_signed_accounts: message.header.num_readonly_signed_accounts,
+            num_readonly_unsigned_accounts: message.header.num_readonly_unsigned_accounts,
+            account_keys: message.account_keys.iter().map(ToString::to_string).collect(),
+            instructions: message
+                .instructions
+                .iter()
+                .map(|ix| CompiledInstructionData {
+                    program_id_index: ix.program_id_index,
+                    accounts: ix.accounts.clone(),
+                    data: Encoding::Base64.encode(&ix.data),
+                })
+                .collect(),
+        }
+    });
+    WithCompiled { response, compiled }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_program::system_program;
+
+    use super::*;
+    use crate::handlers::transfer::{build_send_token, SendTokenRequest, SendTokenResponse};
+
+    fn built_program_id(token_program: &str) -> String {
+        config::init_for_tests();
+        let request: SendTokenRequest = serde_json::from_value(serde_json::json!({
+            "destination": Pubkey::new_unique().to_string(),
+            "mint": Pubkey::new_unique().to_string(),
+            "owner": Pubkey::new_unique().to_string(),
+            "amount": 1,
+            "tokenProgram": token_program,
+        }))
+        .unwrap();
+        match build_send_token(&request).unwrap().instructions {
+            SendTokenResponse::Instruction(ix) => ix.program_id,
+            SendTokenResponse::Sequence(_) => panic!("expected a single instruction"),
+        }
+    }
+
+    #[test]
+    fn token_program_names_select_program() {
+        assert_eq!(built_program_id("spl-token"), spl_token::id().to_string());
+        assert_eq!(built_program_id("token"), spl_token::id().to_string());
+        assert_eq!(built_program_id("token-2022"), spl_token_2022::id().to_string());
+    }
+
+    #[test]
+    fn token_program_ids_select_program() {
+        for id in [spl_token::id(), spl_token_2022::id()] {
+            assert_eq!(built_program_id(&id.to_string()), id.to_string());
+            assert_eq!(TokenProgram::from_str(&id.to_string()).unwrap().id(), id);
+        }
+    }
+
+    #[test]
+    fn token_program_rejects_other_program_ids() {
+        let other = serde_json::json!(system_program::id().to_string());
+        assert!(serde_json::from_value::<TokenProgram>(other).is_err());
+        assert!(TokenProgram::from_str(&system_program::id().to_string()).is_err());
+    }
+
+    fn batch_total(amounts: &[u64]) -> Result<u64, AppError> {
+        amounts.iter().try_fold(0, |total, &amount| checked_add_amount(total, amount))
+    }
+
+    #[test]
+    fn batch_total_reaches_u64_max() {
+        assert_eq!(batch_total(&[u64::MAX - 1, 1]).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn batch_total_past_u64_max_overflows() {
+        let err = batch_total(&[u64::MAX - 1, 1, 1]).unwrap_err();
+        assert_eq!(err.code, ErrorCode::AmountOverflow);
+        assert_eq!(err.message, "amount overflow");
+    }
+
+    #[test]
+    fn scaled_amount_past_u64_max_overflows() {
+        assert_eq!(checked_mul_amount(u64::MAX / 10, 10).unwrap(), u64::MAX - 5);
+        let err = checked_mul_amount(u64::MAX / 10 + 1, 10).unwrap_err();
+        assert_eq!(err.code, ErrorCode::AmountOverflow);
+    }
+
+    #[test]
+    fn parse_pubkey_trims_trailing_newlines() {
+        let pubkey = Pubkey::new_unique();
+        for value in [format!("{}\n", pubkey), format!("  {}\r\n\n", pubkey)] {
+            assert_eq!(parse_pubkey(&value, "owner").unwrap(), pubkey);
+        }
+    }
+}